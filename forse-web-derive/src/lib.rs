@@ -0,0 +1,358 @@
+// `#[derive(ToNode)]` for structs with named fields, see `forse_web::node::ToNode`
+//
+// container attributes, one layout:
+// #[node(dl)]                   <dl> with a <dt> label and <dd> value per field, the default
+// #[node(table)]                <table> with a row per field, also implements `TableRow` for `node::table`
+// #[node(tag = "article.user")] element from the selector with a child element per field
+//
+// field attributes:
+// #[node(skip)]
+// #[node(label = "E-mail")]     instead of the humanized field name
+// #[node(label_key = "user.email")] translated label, see `i18n::TranslationNode`
+// #[node(tag = "h2.name")]      element of the field with `#[node(tag = ...)]` on the struct, "div" by default
+// #[node(with = "path::to_fn")] fn(&FieldType) -> impl ToNode instead of the `ToNode` impl of the field
+//
+// `#[derive(FromForm)]` for structs with named fields, see `forse_web::form::FromForm`,
+// each field is read with `Submission::get` and all errors are collected
+// #[form(rename = "e-mail")]    form field name instead of the struct field name
+//
+// `#[derive(ToAttributeEntries)]` for structs with named fields, see `forse_web::attribute::data` and `aria`,
+// each field is an entry named after the field, `Option` fields are left out when they're `None`
+// #[attribute(skip)]
+// #[attribute(rename = "userId")] entry name instead of the struct field name
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, FieldsNamed, LitStr, Path};
+
+#[proc_macro_derive(ToNode, attributes(node))]
+pub fn derive_to_node(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(FromForm, attributes(form))]
+pub fn derive_from_form(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_form(input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(ToAttributeEntries, attributes(attribute))]
+pub fn derive_to_attribute_entries(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_to_attribute_entries(input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+enum Layout {
+    DefinitionList,
+    Table,
+    Markup(String),
+}
+
+struct Field {
+    ident: syn::Ident,
+    label: TokenStream2,
+    tag: String,
+    with: Option<Path>,
+}
+
+fn named_fields<'a>(input: &'a DeriveInput, derive: &str) -> syn::Result<&'a FieldsNamed> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields),
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                format!("{derive} can only be derived for structs with named fields"),
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("{derive} can only be derived for structs"),
+        )),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = named_fields(&input, "ToNode")?;
+
+    let mut layout = Layout::DefinitionList;
+    for attribute in input
+        .attrs
+        .iter()
+        .filter(|item| item.path().is_ident("node"))
+    {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("dl") {
+                layout = Layout::DefinitionList;
+            } else if meta.path.is_ident("table") {
+                layout = Layout::Table;
+            } else if meta.path.is_ident("tag") {
+                layout = Layout::Markup(meta.value()?.parse::<LitStr>()?.value());
+            } else {
+                return Err(meta.error("expected `dl`, `table` or `tag = \"...\"`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let mut node_fields = vec![];
+    for field in &fields.named {
+        let ident = field.ident.clone().expect("named field");
+        let mut skip = false;
+        let mut label = None;
+        let mut tag = "div".to_string();
+        let mut with = None;
+        for attribute in field
+            .attrs
+            .iter()
+            .filter(|item| item.path().is_ident("node"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("label") {
+                    let value = meta.value()?.parse::<LitStr>()?;
+                    label = Some(quote!(::forse_web::node::ToNode::to_node(&#value)));
+                } else if meta.path.is_ident("label_key") {
+                    let value = meta.value()?.parse::<LitStr>()?;
+                    label = Some(quote!(::forse_web::node::ToNode::to_node(
+                        &::forse_web::i18n::TranslationNode::new(#value)
+                    )));
+                } else if meta.path.is_ident("tag") {
+                    tag = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("with") {
+                    with = Some(meta.value()?.parse::<LitStr>()?.parse::<Path>()?);
+                } else {
+                    return Err(
+                        meta.error("expected `skip`, `label`, `label_key`, `tag` or `with`")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        if skip {
+            continue;
+        }
+        let label = label.unwrap_or_else(|| {
+            let value = humanize(&ident.to_string());
+            quote!(::forse_web::node::ToNode::to_node(&#value))
+        });
+        node_fields.push(Field {
+            ident,
+            label,
+            tag,
+            with,
+        });
+    }
+
+    let values: Vec<TokenStream2> = node_fields
+        .iter()
+        .map(|field| {
+            let ident = &field.ident;
+            match &field.with {
+                Some(path) => quote!(::forse_web::node::ToNode::to_node(&#path(&self.#ident))),
+                None => quote!(::forse_web::node::ToNode::to_node(&self.#ident)),
+            }
+        })
+        .collect();
+    let labels: Vec<&TokenStream2> = node_fields.iter().map(|field| &field.label).collect();
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    for parameter in generics.type_params_mut() {
+        parameter
+            .bounds
+            .push(syn::parse_quote!(::forse_web::node::ToNode));
+    }
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    let elem = quote!(::forse_web::node::elem);
+
+    let body = match &layout {
+        Layout::DefinitionList => quote! {
+            #elem("dl", None, vec![
+                #(#elem("dt", None, #labels), #elem("dd", None, #values)),*
+            ])
+        },
+        Layout::Table => quote! {
+            #elem("table", None, #elem("tbody", None, vec![
+                #(#elem("tr", None, vec![
+                    #elem("th[scope=row]", None, #labels),
+                    #elem("td", None, #values),
+                ])),*
+            ]))
+        },
+        Layout::Markup(selector) => {
+            let tags = node_fields.iter().map(|field| &field.tag);
+            quote! {
+                #elem(#selector, None, vec![#(#elem(#tags, None, #values)),*])
+            }
+        }
+    };
+    let table_row = match &layout {
+        Layout::Table => quote! {
+            impl #impl_generics ::forse_web::node::TableRow for #name #type_generics #where_clause {
+                fn header() -> Vec<::forse_web::node::Node> {
+                    vec![#(#labels),*]
+                }
+
+                fn cells(&self) -> Vec<::forse_web::node::Node> {
+                    vec![#(#values),*]
+                }
+            }
+        },
+        Layout::DefinitionList | Layout::Markup(_) => quote!(),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::forse_web::node::ToNode for #name #type_generics #where_clause {
+            fn to_node(&self) -> ::forse_web::node::Node {
+                #body
+            }
+        }
+
+        #table_row
+    })
+}
+
+fn expand_from_form(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = named_fields(&input, "FromForm")?;
+    let mut idents = vec![];
+    let mut types = vec![];
+    let mut names = vec![];
+    for field in &fields.named {
+        let ident = field.ident.clone().expect("named field");
+        let mut name = ident.to_string().trim_start_matches("r#").to_string();
+        for attribute in field
+            .attrs
+            .iter()
+            .filter(|item| item.path().is_ident("form"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                } else {
+                    return Err(meta.error("expected `rename = \"...\"`"));
+                }
+                Ok(())
+            })?;
+        }
+        idents.push(ident);
+        types.push(&field.ty);
+        names.push(name);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::forse_web::form::FromForm for #name #type_generics #where_clause {
+            fn from_form(
+                submission: &::forse_web::form::Submission,
+            ) -> ::std::result::Result<Self, ::forse_web::form::FormErrors> {
+                let mut errors = ::forse_web::form::FormErrors::new();
+                #(
+                    let #idents = submission
+                        .get::<#types>(#names)
+                        .map_err(|error| errors.merge(error))
+                        .ok();
+                )*
+                match (#(#idents,)*) {
+                    (#(::std::option::Option::Some(#idents),)*) => ::std::result::Result::Ok(Self {
+                        #(#idents),*
+                    }),
+                    _ => ::std::result::Result::Err(errors),
+                }
+            }
+        }
+    })
+}
+
+fn expand_to_attribute_entries(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = named_fields(&input, "ToAttributeEntries")?;
+    let mut entries = vec![];
+    for field in &fields.named {
+        let ident = field.ident.clone().expect("named field");
+        let mut name = ident.to_string().trim_start_matches("r#").to_string();
+        let mut skip = false;
+        for attribute in field
+            .attrs
+            .iter()
+            .filter(|item| item.path().is_ident("attribute"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                } else {
+                    return Err(meta.error("expected `skip` or `rename = \"...\"`"));
+                }
+                Ok(())
+            })?;
+        }
+        if skip {
+            continue;
+        }
+        let value = quote!(::forse_web::attribute::ToAttributeValue::to_attribute_value(value));
+        entries.push(if is_option(&field.ty) {
+            quote! {
+                if let ::std::option::Option::Some(value) = &self.#ident {
+                    entries.push((#name.to_string(), #value));
+                }
+            }
+        } else {
+            quote! {
+                let value = &self.#ident;
+                entries.push((#name.to_string(), #value));
+            }
+        });
+    }
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    for parameter in generics.type_params_mut() {
+        parameter
+            .bounds
+            .push(syn::parse_quote!(::forse_web::attribute::ToAttributeValue));
+    }
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::forse_web::attribute::ToAttributeEntries for #name #type_generics #where_clause {
+            fn to_attribute_entries(
+                &self,
+            ) -> Vec<(String, ::forse_web::attribute::AttributeValue)> {
+                let mut entries = vec![];
+                #(#entries)*
+                entries
+            }
+        }
+    })
+}
+
+// only the written type is known, so aliases of `Option` aren't detected
+fn is_option(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+// "first_name" -> "First name"
+fn humanize(name: &str) -> String {
+    let name = name.trim_start_matches("r#").replace('_', " ");
+    let name = name.trim();
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
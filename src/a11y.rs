@@ -0,0 +1,441 @@
+use std::collections::HashSet;
+
+use crate::{
+    aria::AriaRole,
+    attribute::{Attribute, AttributeValue},
+    node::{flatten_children, Node, TagNode},
+    site::Context,
+    validate::{element_position, location, MISSING_ALT_MESSAGE},
+};
+
+// https://www.w3.org/TR/wai-aria-1.2/#state_prop_def
+pub const ARIA_ATTRIBUTES: &[&str] = &[
+    "aria-activedescendant",
+    "aria-atomic",
+    "aria-autocomplete",
+    "aria-braillelabel",
+    "aria-brailleroledescription",
+    "aria-busy",
+    "aria-checked",
+    "aria-colcount",
+    "aria-colindex",
+    "aria-colindextext",
+    "aria-colspan",
+    "aria-controls",
+    "aria-current",
+    "aria-describedby",
+    "aria-description",
+    "aria-details",
+    "aria-disabled",
+    "aria-dropeffect",
+    "aria-errormessage",
+    "aria-expanded",
+    "aria-flowto",
+    "aria-grabbed",
+    "aria-haspopup",
+    "aria-hidden",
+    "aria-invalid",
+    "aria-keyshortcuts",
+    "aria-label",
+    "aria-labelledby",
+    "aria-level",
+    "aria-live",
+    "aria-modal",
+    "aria-multiline",
+    "aria-multiselectable",
+    "aria-orientation",
+    "aria-owns",
+    "aria-placeholder",
+    "aria-posinset",
+    "aria-pressed",
+    "aria-readonly",
+    "aria-relevant",
+    "aria-required",
+    "aria-roledescription",
+    "aria-rowcount",
+    "aria-rowindex",
+    "aria-rowindextext",
+    "aria-rowspan",
+    "aria-selected",
+    "aria-setsize",
+    "aria-sort",
+    "aria-valuemax",
+    "aria-valuemin",
+    "aria-valuenow",
+    "aria-valuetext",
+];
+
+const INTERACTIVE_ELEMENTS: &[&str] = &[
+    "a", "button", "input", "select", "textarea", "summary", "option", "label", "details",
+];
+
+const INTERACTIVE_ROLES: &[&str] = &[
+    "button",
+    "checkbox",
+    "link",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "option",
+    "radio",
+    "switch",
+    "tab",
+    "treeitem",
+];
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum IssueKind {
+    // has an event handler but no keyboard access or role
+    ClickableNonInteractive,
+    MissingLabel,
+    MissingAlt,
+    InvalidAriaAttribute(String),
+    InvalidRole(String),
+    // e.g. an h4 right after an h2
+    HeadingLevelSkip { from: u8, to: u8 },
+}
+
+#[derive(Clone, Debug)]
+pub struct Issue {
+    pub kind: IssueKind,
+    // same format as `validate::Diagnostic::path`
+    pub path: Vec<usize>,
+    pub location: String,
+    pub message: String,
+}
+
+pub fn check(node: &Node, context: &Context) -> Vec<Issue> {
+    let roots = flatten_children(std::slice::from_ref(node), context);
+    let mut checker = Checker {
+        context,
+        issues: vec![],
+        label_targets: HashSet::new(),
+        last_heading_level: None,
+    };
+    for root in &roots {
+        checker.collect_label_targets(root);
+    }
+    for (index, root) in roots.iter().enumerate() {
+        let (path, position) = if roots.len() > 1 {
+            (vec![index], Some(element_position(&roots, index)))
+        } else {
+            (vec![], None)
+        };
+        checker.visit(root, &path, position, &[]);
+    }
+    checker.issues
+}
+
+// for tests of route output, panics with the list of issues
+pub fn assert_accessible(node: &Node, context: &Context) {
+    let issues = check(node, context);
+    if !issues.is_empty() {
+        let lines: Vec<String> = issues
+            .iter()
+            .map(|issue| format!("{}: {}", issue.location, issue.message))
+            .collect();
+        panic!("Accessibility issues:\n{}", lines.join("\n"));
+    }
+}
+
+struct Checker<'a> {
+    context: &'a Context,
+    issues: Vec<Issue>,
+    // ids referenced by <label for="...">
+    label_targets: HashSet<String>,
+    last_heading_level: Option<u8>,
+}
+
+impl Checker<'_> {
+    fn collect_label_targets(&mut self, node: &Node) {
+        if let Node::Tag(elem) = node {
+            if let Some(properties) = &elem.properties {
+                if properties.tag == "label" {
+                    if let Some(target) = string_attribute(&properties.attributes, "for") {
+                        self.label_targets.insert(target.to_owned());
+                    }
+                }
+            }
+            for child in flatten_children(&elem.children, self.context) {
+                self.collect_label_targets(&child);
+            }
+        }
+    }
+
+    fn visit(&mut self, node: &Node, path: &[usize], position: Option<usize>, ancestors: &[&str]) {
+        let Node::Tag(TagNode {
+            properties: Some(properties),
+            children,
+        }) = node
+        else {
+            return;
+        };
+        let tag = properties.tag.as_str();
+        let attributes = &properties.attributes;
+        let has = |key: &str| attributes.iter().any(|item| item.key == key);
+        let location = location(ancestors, tag, position);
+        let mut issues = vec![];
+        // hidden from assistive technology, `hidden` false isn't rendered
+        let is_hidden = attributes.iter().any(|item| match &item.value {
+            AttributeValue::Bool(value) => item.key == "hidden" && *value,
+            AttributeValue::String(value) => {
+                item.key == "hidden"
+                    || (item.key == "aria-hidden" && value.eq_ignore_ascii_case("true"))
+            }
+            _ => false,
+        });
+
+        let role = string_attribute(attributes, "role");
+        if let Some(role) = role {
+            for token in role.split_whitespace() {
+                if !AriaRole::ALL.iter().any(|item| item.as_str() == token) {
+                    issues.push((
+                        IssueKind::InvalidRole(token.to_string()),
+                        format!("\"{token}\" is not an aria role"),
+                    ));
+                }
+            }
+        }
+        for attribute in attributes {
+            let key = attribute.key.to_lowercase();
+            if key.starts_with("aria-") && !ARIA_ATTRIBUTES.contains(&key.as_str()) {
+                let message = format!("\"{key}\" is not an aria attribute");
+                issues.push((IssueKind::InvalidAriaAttribute(key), message));
+            }
+        }
+
+        // a form is submitted with its buttons and inputs
+        let has_handler = attributes.iter().any(|attribute| {
+            let key = attribute.key.to_lowercase();
+            key.starts_with("on")
+                && key != "onsubmit"
+                && key != "onreset"
+                && matches!(attribute.value, AttributeValue::EventAction(_))
+        });
+        let has_interactive_role = role
+            .map(|role| {
+                role.split_whitespace()
+                    .any(|token| INTERACTIVE_ROLES.contains(&token))
+            })
+            .unwrap_or(false);
+        if has_handler
+            && !INTERACTIVE_ELEMENTS.contains(&tag)
+            && !(has_interactive_role && has("tabindex"))
+        {
+            issues.push((
+                IssueKind::ClickableNonInteractive,
+                format!("<{tag}> has an event handler, use a button or link, or add a role and tabindex"),
+            ));
+        }
+
+        let input_type = string_attribute(attributes, "type").unwrap_or("text");
+        let needs_label = match tag {
+            "input" => !["hidden", "submit", "reset", "button", "image"].contains(&input_type),
+            "select" | "textarea" => true,
+            _ => false,
+        };
+        let is_labelled = has("aria-label")
+            || has("aria-labelledby")
+            || has("title")
+            || ancestors.contains(&"label")
+            || string_attribute(attributes, "id").is_some_and(|id| self.label_targets.contains(id));
+        if needs_label && !is_labelled && !is_hidden {
+            issues.push((
+                IssueKind::MissingLabel,
+                format!("<{tag}> has no label, aria-label or aria-labelledby"),
+            ));
+        }
+
+        let needs_alt = tag == "img" || (tag == "input" && input_type == "image");
+        if needs_alt && !has("alt") && !is_hidden {
+            issues.push((IssueKind::MissingAlt, MISSING_ALT_MESSAGE.to_string()));
+        }
+
+        if let Some(level) = heading_level(tag) {
+            if let Some(last_level) = self.last_heading_level {
+                if level > last_level + 1 {
+                    issues.push((
+                        IssueKind::HeadingLevelSkip {
+                            from: last_level,
+                            to: level,
+                        },
+                        format!("Heading level skips from h{last_level} to h{level}"),
+                    ));
+                }
+            }
+            self.last_heading_level = Some(level);
+        }
+
+        for (kind, message) in issues {
+            self.issues.push(Issue {
+                kind,
+                path: path.to_vec(),
+                location: location.clone(),
+                message,
+            });
+        }
+
+        // hidden subtrees and foreign content aren't checked
+        if tag == "svg" || tag == "math" || is_hidden {
+            return;
+        }
+        let mut child_ancestors = ancestors.to_vec();
+        child_ancestors.push(tag);
+        let children = flatten_children(children, self.context);
+        for (index, child) in children.iter().enumerate() {
+            let mut child_path = path.to_vec();
+            child_path.push(index);
+            let position = Some(element_position(&children, index));
+            self.visit(child, &child_path, position, &child_ancestors);
+        }
+    }
+}
+
+fn string_attribute<'a>(attributes: &'a [Attribute], key: &str) -> Option<&'a str> {
+    attributes.iter().find_map(|item| match &item.value {
+        AttributeValue::String(value) if item.key == key => Some(value.as_str()),
+        _ => None,
+    })
+}
+
+fn heading_level(tag: &str) -> Option<u8> {
+    match tag {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event::EventAction, node::elem};
+
+    fn kinds(node: &Node) -> Vec<IssueKind> {
+        check(node, &Context::new("/"))
+            .into_iter()
+            .map(|item| item.kind)
+            .collect()
+    }
+
+    fn image_in(attributes: Vec<Attribute>) -> Node {
+        elem("div", Some(attributes), elem("img", None, Node::None))
+    }
+
+    #[test]
+    fn hidden_subtrees_are_skipped() {
+        assert_eq!(
+            kinds(&image_in(vec![Attribute::from("hidden", true)])),
+            vec![]
+        );
+        assert_eq!(
+            kinds(&image_in(vec![Attribute::from("hidden", false)])),
+            vec![IssueKind::MissingAlt]
+        );
+    }
+
+    #[test]
+    fn aria_hidden_subtrees_are_skipped() {
+        assert_eq!(
+            kinds(&image_in(vec![Attribute::from("aria-hidden", "true")])),
+            vec![]
+        );
+        assert_eq!(
+            kinds(&elem("img[aria-hidden=true]", None, Node::None)),
+            vec![]
+        );
+        assert_eq!(
+            kinds(&image_in(vec![Attribute::from("aria-hidden", "false")])),
+            vec![IssueKind::MissingAlt]
+        );
+    }
+
+    #[test]
+    fn handlers_need_an_interactive_element_or_role() {
+        let handler = |selector: &str| {
+            let action = EventAction::ajax_default("/a", None);
+            elem(
+                selector,
+                Some(vec![Attribute::from("onclick", action)]),
+                "x",
+            )
+        };
+        assert_eq!(
+            kinds(&handler("div")),
+            vec![IssueKind::ClickableNonInteractive]
+        );
+        assert_eq!(
+            kinds(&handler("div[role=button]")),
+            vec![IssueKind::ClickableNonInteractive]
+        );
+        assert_eq!(kinds(&handler("div[role=button][tabindex=0]")), vec![]);
+        assert_eq!(kinds(&handler("button")), vec![]);
+        let form = elem(
+            "form",
+            Some(vec![Attribute::from(
+                "onsubmit",
+                EventAction::ajax_default("/a", None),
+            )]),
+            Node::None,
+        );
+        assert_eq!(kinds(&form), vec![]);
+    }
+
+    #[test]
+    fn form_controls_need_a_label() {
+        assert_eq!(
+            kinds(&elem("input", None, Node::None)),
+            vec![IssueKind::MissingLabel]
+        );
+        assert_eq!(kinds(&elem("input[type=hidden]", None, Node::None)), vec![]);
+        assert_eq!(
+            kinds(&elem("textarea[aria-label=Comment]", None, Node::None)),
+            vec![]
+        );
+        let node = elem(
+            "div",
+            None,
+            (
+                elem("label[for=name]", None, "Name"),
+                elem("input#name", None, Node::None),
+                elem("label", None, ("Age", elem("input", None, Node::None))),
+                elem("select#other", None, Node::None),
+            ),
+        );
+        let issues = check(&node, &Context::new("/"));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::MissingLabel);
+        assert_eq!(issues[0].location, "div > select:nth-child(4)");
+    }
+
+    #[test]
+    fn heading_levels_can_not_be_skipped() {
+        let node = elem(
+            "main",
+            None,
+            (
+                elem("h1", None, "a"),
+                elem("h2", None, "b"),
+                elem("section", None, elem("h4", None, "c")),
+                elem("h2", None, "d"),
+            ),
+        );
+        assert_eq!(
+            kinds(&node),
+            vec![IssueKind::HeadingLevelSkip { from: 2, to: 4 }]
+        );
+    }
+
+    #[test]
+    fn roles_come_from_aria_role() {
+        let node = elem("div[role=tab tabs]", None, Node::None);
+        assert_eq!(
+            kinds(&node),
+            vec![IssueKind::InvalidRole("tabs".to_string())]
+        );
+    }
+}
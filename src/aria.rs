@@ -0,0 +1,233 @@
+use crate::{
+    attribute::{Attribute, AttributeValue, ToAttributeValue},
+    html::attribute_enum,
+};
+
+// https://www.w3.org/TR/wai-aria-1.2/#role_definitions, abstract roles left out,
+// also the roles accepted by `a11y::check`, e.g. `Attribute::from("role", AriaRole::Tab)`
+attribute_enum!(AriaRole {
+    Alert => "alert",
+    AlertDialog => "alertdialog",
+    Application => "application",
+    Article => "article",
+    Banner => "banner",
+    Blockquote => "blockquote",
+    Button => "button",
+    Caption => "caption",
+    Cell => "cell",
+    Checkbox => "checkbox",
+    Code => "code",
+    ColumnHeader => "columnheader",
+    Combobox => "combobox",
+    Complementary => "complementary",
+    ContentInfo => "contentinfo",
+    Definition => "definition",
+    Deletion => "deletion",
+    Dialog => "dialog",
+    Directory => "directory",
+    Document => "document",
+    Emphasis => "emphasis",
+    Feed => "feed",
+    Figure => "figure",
+    Form => "form",
+    Generic => "generic",
+    Grid => "grid",
+    GridCell => "gridcell",
+    Group => "group",
+    Heading => "heading",
+    Img => "img",
+    Insertion => "insertion",
+    Link => "link",
+    List => "list",
+    ListBox => "listbox",
+    ListItem => "listitem",
+    Log => "log",
+    Main => "main",
+    Marquee => "marquee",
+    Math => "math",
+    Menu => "menu",
+    MenuBar => "menubar",
+    MenuItem => "menuitem",
+    MenuItemCheckbox => "menuitemcheckbox",
+    MenuItemRadio => "menuitemradio",
+    Meter => "meter",
+    Navigation => "navigation",
+    None => "none",
+    Note => "note",
+    Option => "option",
+    Paragraph => "paragraph",
+    Presentation => "presentation",
+    ProgressBar => "progressbar",
+    Radio => "radio",
+    RadioGroup => "radiogroup",
+    Region => "region",
+    Row => "row",
+    RowGroup => "rowgroup",
+    RowHeader => "rowheader",
+    ScrollBar => "scrollbar",
+    Search => "search",
+    SearchBox => "searchbox",
+    Separator => "separator",
+    Slider => "slider",
+    SpinButton => "spinbutton",
+    Status => "status",
+    Strong => "strong",
+    Subscript => "subscript",
+    Superscript => "superscript",
+    Switch => "switch",
+    Tab => "tab",
+    Table => "table",
+    TabList => "tablist",
+    TabPanel => "tabpanel",
+    Term => "term",
+    TextBox => "textbox",
+    Time => "time",
+    Timer => "timer",
+    Toolbar => "toolbar",
+    Tooltip => "tooltip",
+    Tree => "tree",
+    TreeGrid => "treegrid",
+    TreeItem => "treeitem",
+});
+
+// values of aria-checked and aria-pressed
+attribute_enum!(Tristate { True => "true", False => "false", Mixed => "mixed" });
+
+attribute_enum!(AriaCurrent {
+    Page => "page",
+    Step => "step",
+    Location => "location",
+    Date => "date",
+    Time => "time",
+    True => "true",
+    False => "false",
+});
+
+attribute_enum!(AriaLive { Off => "off", Polite => "polite", Assertive => "assertive" });
+
+attribute_enum!(AriaHasPopup {
+    True => "true",
+    False => "false",
+    Menu => "menu",
+    ListBox => "listbox",
+    Tree => "tree",
+    Grid => "grid",
+    Dialog => "dialog",
+});
+
+attribute_enum!(AriaSort { Ascending => "ascending", Descending => "descending", None => "none", Other => "other" });
+
+attribute_enum!(AriaOrientation { Horizontal => "horizontal", Vertical => "vertical" });
+
+// typed aria states and properties, id references are space separated lists of ids
+#[derive(Clone, PartialEq, Debug)]
+pub enum AriaState {
+    Atomic(bool),
+    Busy(bool),
+    Checked(Tristate),
+    Controls(String),
+    Current(AriaCurrent),
+    DescribedBy(String),
+    Description(String),
+    Disabled(bool),
+    ErrorMessage(String),
+    Expanded(bool),
+    HasPopup(AriaHasPopup),
+    Hidden(bool),
+    Invalid(bool),
+    Label(String),
+    LabelledBy(String),
+    Level(u32),
+    Live(AriaLive),
+    Modal(bool),
+    Orientation(AriaOrientation),
+    Owns(String),
+    PosInSet(u32),
+    Pressed(Tristate),
+    ReadOnly(bool),
+    Required(bool),
+    Selected(bool),
+    SetSize(i32),
+    Sort(AriaSort),
+    ValueMax(f64),
+    ValueMin(f64),
+    ValueNow(f64),
+    ValueText(String),
+}
+
+impl AriaState {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Atomic(_) => "aria-atomic",
+            Self::Busy(_) => "aria-busy",
+            Self::Checked(_) => "aria-checked",
+            Self::Controls(_) => "aria-controls",
+            Self::Current(_) => "aria-current",
+            Self::DescribedBy(_) => "aria-describedby",
+            Self::Description(_) => "aria-description",
+            Self::Disabled(_) => "aria-disabled",
+            Self::ErrorMessage(_) => "aria-errormessage",
+            Self::Expanded(_) => "aria-expanded",
+            Self::HasPopup(_) => "aria-haspopup",
+            Self::Hidden(_) => "aria-hidden",
+            Self::Invalid(_) => "aria-invalid",
+            Self::Label(_) => "aria-label",
+            Self::LabelledBy(_) => "aria-labelledby",
+            Self::Level(_) => "aria-level",
+            Self::Live(_) => "aria-live",
+            Self::Modal(_) => "aria-modal",
+            Self::Orientation(_) => "aria-orientation",
+            Self::Owns(_) => "aria-owns",
+            Self::PosInSet(_) => "aria-posinset",
+            Self::Pressed(_) => "aria-pressed",
+            Self::ReadOnly(_) => "aria-readonly",
+            Self::Required(_) => "aria-required",
+            Self::Selected(_) => "aria-selected",
+            Self::SetSize(_) => "aria-setsize",
+            Self::Sort(_) => "aria-sort",
+            Self::ValueMax(_) => "aria-valuemax",
+            Self::ValueMin(_) => "aria-valuemin",
+            Self::ValueNow(_) => "aria-valuenow",
+            Self::ValueText(_) => "aria-valuetext",
+        }
+    }
+
+    // aria booleans are the strings "true" and "false", not boolean attributes
+    pub fn value(&self) -> String {
+        match self {
+            Self::Atomic(value)
+            | Self::Busy(value)
+            | Self::Disabled(value)
+            | Self::Expanded(value)
+            | Self::Hidden(value)
+            | Self::Invalid(value)
+            | Self::Modal(value)
+            | Self::ReadOnly(value)
+            | Self::Required(value)
+            | Self::Selected(value) => value.to_string(),
+            Self::Controls(value)
+            | Self::DescribedBy(value)
+            | Self::Description(value)
+            | Self::ErrorMessage(value)
+            | Self::Label(value)
+            | Self::LabelledBy(value)
+            | Self::Owns(value)
+            | Self::ValueText(value) => value.to_owned(),
+            Self::Level(value) | Self::PosInSet(value) => value.to_string(),
+            Self::SetSize(value) => value.to_string(),
+            Self::ValueMax(value) | Self::ValueMin(value) | Self::ValueNow(value) => {
+                value.to_string()
+            }
+            Self::Checked(value) | Self::Pressed(value) => value.as_str().to_string(),
+            Self::Current(value) => value.as_str().to_string(),
+            Self::HasPopup(value) => value.as_str().to_string(),
+            Self::Live(value) => value.as_str().to_string(),
+            Self::Orientation(value) => value.as_str().to_string(),
+            Self::Sort(value) => value.as_str().to_string(),
+        }
+    }
+
+    pub fn to_attribute(&self) -> Attribute {
+        Attribute::from(self.key(), self.value())
+    }
+}
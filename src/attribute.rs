@@ -1,9 +1,20 @@
-use crate::event::EventAction;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    class_list::ClassList,
+    event::EventAction,
+    render::{html_attribute, RenderOptions, Syntax},
+    utils::{adjust_case, kebab_case, to_js_string, SVG_CASED_ATTRIBUTES},
+};
 
 #[derive(Clone)]
 pub enum AttributeValue {
     String(String),
+    // boolean attributes, e.g. `disabled`, false leaves the attribute out of the html
+    Bool(bool),
     EventAction(EventAction),
+    // classes and scoped styles, merged into one class attribute by `parse_elem_properties`
+    ClassList(ClassList),
 }
 
 #[derive(Clone)]
@@ -27,32 +38,253 @@ impl Attribute {
         }
     }
 
-    // todo: consider escaping doublequotes
     pub fn to_inline_html_item(&self) -> String {
-        let key = &self.key;
+        self.to_html_item(&RenderOptions::default())
+    }
+
+    pub fn to_html_item(&self, options: &RenderOptions) -> String {
+        // the mithril name of the class attribute
+        let key = if self.key == "className" {
+            "class"
+        } else {
+            &self.key
+        };
         match &self.value {
-            AttributeValue::String(value) => {
-                format!("{key}=\"{value}\"")
+            AttributeValue::String(value) => html_attribute(key, value, options),
+            AttributeValue::ClassList(list) => {
+                html_attribute(key, &list.to_class_string(), options)
             }
-            AttributeValue::EventAction(_) => String::from(""),
+            AttributeValue::Bool(true) => match options.syntax {
+                Syntax::Html => key.to_owned(),
+                Syntax::Xhtml => html_attribute(key, key, options),
+            },
+            AttributeValue::Bool(false) | AttributeValue::EventAction(_) => String::from(""),
         }
     }
 
     // todo: use proper json
     pub fn to_json_object_item(&self, function_name: &str) -> String {
-        let key = &self.key;
+        // mithril merges the selector classes with `className`
+        let key = if self.key == "class" {
+            "className"
+        } else {
+            &self.key
+        };
+        let key = to_js_string(key);
         match &self.value {
-            AttributeValue::String(value) => {
-                format!("\"{key}\":\"{value}\"")
+            // values can come from user input, e.g. the fields of a submitted form
+            AttributeValue::String(value) => format!("{key}:{}", to_js_string(value)),
+            AttributeValue::Bool(value) => format!("{key}:{value}"),
+            AttributeValue::ClassList(list) => {
+                format!("{key}:{}", to_js_string(&list.to_class_string()))
             }
+            // the handler itself, mithril attaches functions as event listeners
             AttributeValue::EventAction(value) => {
                 let attribute_value = value.hyperscript_action.to_hyperscript(function_name);
-                format!("\"{key}\":\"{attribute_value}\"")
+                format!("{key}:{attribute_value}")
+            }
+        }
+    }
+
+    // "key":value pair for `Node::to_json`, event actions are described as data
+    pub fn to_json_item(&self) -> String {
+        let key = to_js_string(&self.key);
+        match &self.value {
+            AttributeValue::String(value) => format!("{key}:{}", to_js_string(value)),
+            AttributeValue::Bool(value) => format!("{key}:{value}"),
+            AttributeValue::ClassList(list) => {
+                format!("{key}:{}", to_js_string(&list.to_class_string()))
+            }
+            AttributeValue::EventAction(value) => {
+                format!("{key}:{}", value.hyperscript_action.to_json())
             }
         }
     }
 }
 
+// html attribute names are case insensitive, keys are lowercased except the svg ones like `viewBox`
+// and mithril's `className`
+pub fn normalize_attribute_key(key: &str) -> String {
+    if key.eq_ignore_ascii_case("className") {
+        "className".to_string()
+    } else {
+        adjust_case(&key.to_ascii_lowercase(), SVG_CASED_ATTRIBUTES)
+    }
+}
+
+// attributes of an element, keys are unique and keep the position of their first insertion,
+// see `normalize_attribute_key`
+#[derive(Clone, Default)]
+pub struct Attributes {
+    items: Vec<Attribute>,
+}
+
+impl Attributes {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&AttributeValue> {
+        let key = normalize_attribute_key(key);
+        self.items
+            .iter()
+            .find(|item| item.key == key)
+            .map(|item| &item.value)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    // replaces the value of an existing key in place, returns the previous value
+    pub fn insert(&mut self, attribute: Attribute) -> Option<AttributeValue> {
+        let key = normalize_attribute_key(&attribute.key);
+        match self.items.iter_mut().find(|item| item.key == key) {
+            Some(item) => Some(std::mem::replace(&mut item.value, attribute.value)),
+            None => {
+                self.items.push(Attribute::new(&key, attribute.value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<AttributeValue> {
+        let key = normalize_attribute_key(key);
+        let index = self.items.iter().position(|item| item.key == key)?;
+        Some(self.items.remove(index).value)
+    }
+
+    pub fn as_slice(&self) -> &[Attribute] {
+        &self.items
+    }
+}
+
+impl std::ops::Deref for Attributes {
+    type Target = [Attribute];
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type Item = &'a Attribute;
+    type IntoIter = std::slice::Iter<'a, Attribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+// later duplicates replace the earlier values
+impl FromIterator<Attribute> for Attributes {
+    fn from_iter<I: IntoIterator<Item = Attribute>>(iter: I) -> Self {
+        let mut attributes = Self::new();
+        for attribute in iter {
+            attributes.insert(attribute);
+        }
+        attributes
+    }
+}
+
+impl AttributeValue {
+    // event actions are never equal, class lists are merged instead of compared
+    pub fn same_as(&self, other: &AttributeValue) -> bool {
+        match (self, other) {
+            (Self::String(value), Self::String(other)) => value == other,
+            (Self::Bool(value), Self::Bool(other)) => value == other,
+            (Self::ClassList(value), Self::ClassList(other)) => value == other,
+            _ => false,
+        }
+    }
+}
+
+// key value pairs for `data` and `aria`, `#[derive(ToAttributeEntries)]` lists the fields of a struct
+pub trait ToAttributeEntries {
+    fn to_attribute_entries(&self) -> Vec<(String, AttributeValue)>;
+}
+
+#[cfg(feature = "derive")]
+pub use forse_web_derive::ToAttributeEntries;
+
+impl<K: AsRef<str>, V: ToAttributeValue> ToAttributeEntries for [(K, V)] {
+    fn to_attribute_entries(&self) -> Vec<(String, AttributeValue)> {
+        self.iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value.to_attribute_value()))
+            .collect()
+    }
+}
+
+impl<K: AsRef<str>, V: ToAttributeValue, const N: usize> ToAttributeEntries for [(K, V); N] {
+    fn to_attribute_entries(&self) -> Vec<(String, AttributeValue)> {
+        self.as_slice().to_attribute_entries()
+    }
+}
+
+impl<K: AsRef<str>, V: ToAttributeValue> ToAttributeEntries for Vec<(K, V)> {
+    fn to_attribute_entries(&self) -> Vec<(String, AttributeValue)> {
+        self.as_slice().to_attribute_entries()
+    }
+}
+
+// sorted by key, the iteration order of a hash map isn't stable
+impl<K: AsRef<str>, V: ToAttributeValue> ToAttributeEntries for HashMap<K, V> {
+    fn to_attribute_entries(&self) -> Vec<(String, AttributeValue)> {
+        let mut entries: Vec<(String, AttributeValue)> = self
+            .iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value.to_attribute_value()))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+}
+
+impl<K: AsRef<str>, V: ToAttributeValue> ToAttributeEntries for BTreeMap<K, V> {
+    fn to_attribute_entries(&self) -> Vec<(String, AttributeValue)> {
+        self.iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value.to_attribute_value()))
+            .collect()
+    }
+}
+
+// `data(&[("userId", 5)])` gives `data-user-id="5"`
+pub fn data(entries: &(impl ToAttributeEntries + ?Sized)) -> Vec<Attribute> {
+    entries
+        .to_attribute_entries()
+        .into_iter()
+        .map(|(key, value)| {
+            let key = kebab_case(&key);
+            let key = key.strip_prefix("data-").unwrap_or(&key);
+            Attribute::new(&format!("data-{key}"), string_boolean(value))
+        })
+        .collect()
+}
+
+// aria names are lowercase without separators, `aria(&[("labelledBy", "title")])` gives `aria-labelledby="title"`
+pub fn aria(entries: &(impl ToAttributeEntries + ?Sized)) -> Vec<Attribute> {
+    entries
+        .to_attribute_entries()
+        .into_iter()
+        .map(|(key, value)| {
+            let key: String = key
+                .chars()
+                .filter(|c| !['-', '_'].contains(c))
+                .flat_map(|c| c.to_lowercase())
+                .collect();
+            let key = key.strip_prefix("aria").unwrap_or(&key);
+            Attribute::new(&format!("aria-{key}"), string_boolean(value))
+        })
+        .collect()
+}
+
+// dataset and aria values are strings, false has to be kept as "false"
+fn string_boolean(value: AttributeValue) -> AttributeValue {
+    match value {
+        AttributeValue::Bool(value) => AttributeValue::String(value.to_string()),
+        value => value,
+    }
+}
+
 pub trait ToAttributeValue {
     fn to_attribute_value(&self) -> AttributeValue;
 }
@@ -80,3 +312,27 @@ impl ToAttributeValue for &str {
         AttributeValue::String(self.to_string())
     }
 }
+
+impl ToAttributeValue for bool {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::Bool(*self)
+    }
+}
+
+impl ToAttributeValue for i32 {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::String(self.to_string())
+    }
+}
+
+impl ToAttributeValue for u32 {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::String(self.to_string())
+    }
+}
+
+impl ToAttributeValue for f64 {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::String(self.to_string())
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{
+    attribute::{AttributeValue, ToAttributeValue},
+    style::StyleSheet,
+};
+
+// value of a class attribute, merged with the selector classes by `parse_elem_properties`:
+// `classes!("button", "active" => is_active, &style).remove("hidden")`
+// classes are split on whitespace and kept once, in order of first use
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct ClassList {
+    pub classes: Vec<String>,
+    // also removed from the selector and the other class attributes of the element
+    pub removed: Vec<String>,
+    pub styles: Vec<StyleSheet>,
+}
+
+impl ClassList {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn class(mut self, classes: impl ToClasses) -> Self {
+        classes.add_to(&mut self);
+        self
+    }
+
+    pub fn class_if(self, classes: impl ToClasses, condition: bool) -> Self {
+        if condition {
+            self.class(classes)
+        } else {
+            self
+        }
+    }
+
+    pub fn remove(mut self, class: &str) -> Self {
+        for class in class.split_whitespace() {
+            self.classes.retain(|item| item != class);
+            if !self.removed.iter().any(|item| item == class) {
+                self.removed.push(class.to_string());
+            }
+        }
+        self
+    }
+
+    pub fn contains(&self, class: &str) -> bool {
+        self.classes.iter().any(|item| item == class)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    // combines two lists, removals of both apply
+    pub fn merge(mut self, other: ClassList) -> Self {
+        for class in other.classes {
+            self.push_class(&class);
+        }
+        for class in other.removed {
+            self = self.remove(&class);
+        }
+        for style in other.styles {
+            self.push_style(style);
+        }
+        self
+    }
+
+    // value of the class attribute
+    pub fn to_class_string(&self) -> String {
+        self.classes.join(" ")
+    }
+
+    fn push_class(&mut self, classes: &str) {
+        for class in classes.split_whitespace() {
+            if !self.contains(class) {
+                self.classes.push(class.to_string());
+            }
+            self.removed.retain(|item| item != class);
+        }
+    }
+
+    fn push_style(&mut self, style: StyleSheet) {
+        self.push_class(&style.class_name());
+        if !self.styles.contains(&style) {
+            self.styles.push(style);
+        }
+    }
+}
+
+pub trait ToClasses {
+    fn add_to(self, list: &mut ClassList);
+}
+
+impl ToClasses for &str {
+    fn add_to(self, list: &mut ClassList) {
+        list.push_class(self);
+    }
+}
+
+impl ToClasses for String {
+    fn add_to(self, list: &mut ClassList) {
+        list.push_class(&self);
+    }
+}
+
+impl ToClasses for &String {
+    fn add_to(self, list: &mut ClassList) {
+        list.push_class(self);
+    }
+}
+
+impl ToClasses for StyleSheet {
+    fn add_to(self, list: &mut ClassList) {
+        list.push_style(self);
+    }
+}
+
+impl ToClasses for &StyleSheet {
+    fn add_to(self, list: &mut ClassList) {
+        list.push_style(self.clone());
+    }
+}
+
+impl ToClasses for ClassList {
+    fn add_to(self, list: &mut ClassList) {
+        *list = std::mem::take(list).merge(self);
+    }
+}
+
+impl ToAttributeValue for ClassList {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::ClassList(self.clone())
+    }
+}
+
+// `classes!("card", "active" => is_active, &style)`, entries with a false condition are left out
+#[macro_export]
+macro_rules! classes {
+    ($($classes:expr $(=> $condition:expr)?),* $(,)?) => {{
+        let list = $crate::class_list::ClassList::new();
+        $(
+            let list = list.class_if($classes, $crate::class_list::all(&[$($condition)?]));
+        )*
+        list
+    }};
+}
+
+// for the optional conditions of `classes!`
+pub fn all(conditions: &[bool]) -> bool {
+    conditions.iter().all(|item| *item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attribute::Attribute, node::elem, site::Context};
+
+    #[test]
+    fn classes_are_split_and_kept_once() {
+        let list = ClassList::new().class("a  b").class(String::from("b c"));
+        assert_eq!(list.to_class_string(), "a b c");
+        assert!(list.contains("c"));
+        assert!(!list.contains("a b"));
+    }
+
+    #[test]
+    fn conditional_entries_are_left_out() {
+        let (is_active, is_hidden) = (true, false);
+        let list = classes!("button", "active" => is_active, "hidden" => is_hidden);
+        assert_eq!(list.to_class_string(), "button active");
+        assert!(classes!().is_empty());
+    }
+
+    #[test]
+    fn removed_classes_apply_to_merged_lists() {
+        let list = classes!("a", "b").remove("a");
+        assert_eq!(list.to_class_string(), "b");
+        // a later class adds it back
+        assert_eq!(list.clone().class("a").to_class_string(), "b a");
+        let merged = classes!("a", "c").merge(list);
+        assert_eq!(merged.to_class_string(), "c b");
+        assert_eq!(merged.removed, vec!["a"]);
+    }
+
+    #[test]
+    fn removals_apply_to_the_selector_classes() {
+        let context = Context::new("/");
+        let node = elem(
+            "div.card.hidden",
+            Some(vec![Attribute::from(
+                "class",
+                classes!("wide").remove("hidden"),
+            )]),
+            "x",
+        );
+        assert_eq!(node.to_html(&context), "<div class=\"card wide\">x</div>");
+    }
+
+    #[test]
+    fn styles_add_their_class() {
+        let style = StyleSheet::new("card").declarations("color: red");
+        let list = classes!("a", &style, &style);
+        assert_eq!(list.to_class_string(), format!("a {}", style.class_name()));
+        assert_eq!(list.styles, vec![style]);
+    }
+}
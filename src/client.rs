@@ -0,0 +1,2 @@
+// defines the global `forse` with `toVnode(m, tree)` and `load(m, url)`, tree being the output of `Node::to_json`
+pub const RUNTIME: &str = include_str!("runtime.js");
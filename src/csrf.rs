@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use crate::{
+    attribute::{Attribute, AttributeValue},
+    event::{AjaxRequestOption, EventAction, HyperscriptAction},
+    node::{Node, NodeProperties, TagNode},
+    site::Context,
+    utils::parse_elem_properties,
+};
+
+// double submit tokens: the token is kept in a cookie and has to come back with every
+// state-changing request, as a form field or a header, which other sites can't set
+//
+// `ContextBuilder::issue_csrf_token` reads the cookie or issues a new token,
+// rendered post forms get it as a hidden input and non-GET ajax actions as a header,
+// `server::RouterService` sets the cookie and rejects requests without the token
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_FIELD: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+
+// 32 random bytes as hex
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|error| error.to_string())?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+// value of the set-cookie header for a token issued with the context
+pub fn cookie_header(token: &str, secure: bool) -> String {
+    let secure = if secure { "; Secure" } else { "" };
+    format!("{CSRF_COOKIE}={token}; Path=/; SameSite=Lax; HttpOnly{secure}")
+}
+
+// from the headers of a tls-terminating proxy, the request uri doesn't have the scheme
+pub fn is_https(context: &Context) -> bool {
+    let forwarded_proto = context
+        .header("x-forwarded-proto")
+        .and_then(|value| value.split(',').next())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("https"));
+    let forwarded = context.header("forwarded").is_some_and(|value| {
+        let first = value.split(',').next().unwrap_or_default();
+        first.split(';').any(|item| {
+            item.trim()
+                .to_ascii_lowercase()
+                .strip_prefix("proto=")
+                .is_some_and(|proto| proto.trim_matches('"') == "https")
+        })
+    });
+    forwarded_proto || forwarded
+}
+
+pub fn is_safe_method(method: &str) -> bool {
+    ["GET", "HEAD", "OPTIONS", "TRACE"]
+        .iter()
+        .any(|item| item.eq_ignore_ascii_case(method))
+}
+
+// safe methods don't need a token, the others need the one of the context in the header or the form
+pub fn verify(context: &Context) -> bool {
+    if is_safe_method(&context.method) {
+        return true;
+    }
+    let Some(token) = &context.csrf_token else {
+        return false;
+    };
+    let submitted = match context.header(CSRF_HEADER) {
+        Some(value) => Some(value.to_string()),
+        None => context
+            .form_params()
+            .into_iter()
+            .find(|(key, _)| key == CSRF_FIELD)
+            .map(|(_, value)| value),
+    };
+    submitted.is_some_and(|submitted| constant_time_eq(submitted.as_bytes(), token.as_bytes()))
+}
+
+// absolute urls to another host than the one of the request, the token must not leak to other sites,
+// without a host header every absolute url counts as another one
+fn is_cross_origin(url: &str, context: &Context) -> bool {
+    let authority = match url.split_once("://") {
+        Some((scheme, rest))
+            if scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || ['+', '-', '.'].contains(&c)) =>
+        {
+            rest
+        }
+        _ => match url.strip_prefix("//") {
+            Some(rest) => rest,
+            None => return false,
+        },
+    };
+    let host = authority.split(['/', '?', '#']).next().unwrap_or_default();
+    // user info isn't part of the host
+    let host = host.rsplit('@').next().unwrap_or_default();
+    !context
+        .header("host")
+        .is_some_and(|request_host| request_host.eq_ignore_ascii_case(host))
+}
+
+// doesn't stop at the first difference, so the time doesn't tell how much of a guess was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0, |result, (a, b)| result | (a ^ b))
+        == 0
+}
+
+// children of a form with the hidden token input in front, `None` if the form doesn't need one:
+// get forms, forms that already have the input and forms posting to other origins
+pub fn form_children(
+    properties: &NodeProperties,
+    children: &[Node],
+    context: &Context,
+) -> Option<Vec<Node>> {
+    let token = context.csrf_token.as_ref()?;
+    if properties.tag != "form" {
+        return None;
+    }
+    let string_attribute = |key: &str| match properties.attributes.get(key) {
+        Some(AttributeValue::String(value)) => Some(value.as_str()),
+        _ => None,
+    };
+    let method = string_attribute("method").unwrap_or("get");
+    let action = string_attribute("action").unwrap_or_default();
+    if is_safe_method(method)
+        || method.eq_ignore_ascii_case("dialog")
+        || is_cross_origin(action, context)
+    {
+        return None;
+    }
+    let has_input = children.iter().any(|child| match child {
+        Node::Tag(TagNode {
+            properties: Some(properties),
+            ..
+        }) => {
+            properties.tag == "input"
+                && matches!(
+                    properties.attributes.get("name"),
+                    Some(AttributeValue::String(name)) if name == CSRF_FIELD
+                )
+        }
+        _ => false,
+    });
+    if has_input {
+        return None;
+    }
+    let input = Node::Tag(TagNode {
+        properties: Some(parse_elem_properties(
+            "input",
+            &Some(vec![
+                Attribute::from("type", "hidden"),
+                Attribute::from("name", CSRF_FIELD),
+                Attribute::from("value", token.as_str()),
+            ]),
+        )),
+        children: vec![],
+    });
+    Some([vec![input], children.to_vec()].concat())
+}
+
+// the action with the token header of the context if it changes state
+pub fn protect_action(action: &HyperscriptAction, context: &Context) -> HyperscriptAction {
+    let Some(token) = &context.csrf_token else {
+        return action.clone();
+    };
+    match action {
+        HyperscriptAction::AjaxRequest { url, options } => {
+            let method = options
+                .iter()
+                .rev()
+                .find_map(|option| match option {
+                    AjaxRequestOption::Method(method) => Some(method.as_str()),
+                    _ => None,
+                })
+                .unwrap_or("GET");
+            if is_safe_method(method) || is_cross_origin(url, context) {
+                return action.clone();
+            }
+            let mut options = options.clone();
+            let headers = options.iter_mut().find_map(|option| match option {
+                AjaxRequestOption::Headers(headers) => Some(headers),
+                _ => None,
+            });
+            match headers {
+                Some(headers) => {
+                    headers
+                        .entry(CSRF_HEADER.to_string())
+                        .or_insert_with(|| token.to_owned());
+                }
+                None => options.push(AjaxRequestOption::Headers(HashMap::from([(
+                    CSRF_HEADER.to_string(),
+                    token.to_owned(),
+                )]))),
+            }
+            HyperscriptAction::AjaxRequest {
+                url: url.to_owned(),
+                options,
+            }
+        }
+    }
+}
+
+// attribute with `protect_action` applied to its event action
+pub fn protect_attribute(attribute: &Attribute, context: &Context) -> Attribute {
+    match &attribute.value {
+        AttributeValue::EventAction(action) => Attribute::new(
+            &attribute.key,
+            AttributeValue::EventAction(EventAction {
+                hyperscript_action: protect_action(&action.hyperscript_action, context),
+                html_action: action.html_action.clone(),
+            }),
+        ),
+        _ => attribute.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::elem;
+
+    fn context() -> Context {
+        Context::builder("/")
+            .header("host", "example.com")
+            .csrf_token("abc")
+            .build()
+    }
+
+    fn form_html(action: &str) -> String {
+        let attributes = vec![
+            Attribute::from("method", "post"),
+            Attribute::from("action", action),
+        ];
+        elem("form", Some(attributes), Node::None).to_html(&context())
+    }
+
+    #[test]
+    fn forms_to_the_request_host_get_the_token() {
+        let input = "<input type=\"hidden\" name=\"csrf_token\" value=\"abc\" />";
+        assert!(form_html("/save").contains(input));
+        assert!(form_html("https://example.com/save").contains(input));
+        assert!(form_html("//EXAMPLE.com?x").contains(input));
+        assert!(!form_html("https://other.com/save").contains(input));
+        assert!(!form_html("//other.com").contains(input));
+        assert!(!form_html("https://example.com@other.com/").contains(input));
+    }
+
+    #[test]
+    fn https_comes_from_the_proxy_headers() {
+        let is_https_with =
+            |key: &str, value: &str| is_https(&Context::builder("/").header(key, value).build());
+        assert!(is_https_with("x-forwarded-proto", "https"));
+        assert!(is_https_with("x-forwarded-proto", "HTTPS, http"));
+        assert!(!is_https_with("x-forwarded-proto", "http"));
+        assert!(is_https_with("forwarded", "for=1.2.3.4;proto=https"));
+        assert!(!is_https_with("forwarded", "proto=http, proto=https"));
+        assert!(!is_https(&Context::new("/")));
+    }
+
+    #[test]
+    fn absolute_urls_are_cross_origin_without_a_host() {
+        let context = Context::builder("/").build();
+        assert!(is_cross_origin("https://example.com/", &context));
+        assert!(!is_cross_origin("/save?next=https://x.com", &context));
+    }
+
+    #[test]
+    fn state_changing_ajax_actions_get_the_header() {
+        let action = |url: &str, method: &str| {
+            HyperscriptAction::ajax_default(
+                url,
+                Some(vec![AjaxRequestOption::Method(method.to_string())]),
+            )
+        };
+        let has_header = |action: &HyperscriptAction| {
+            let HyperscriptAction::AjaxRequest { options, .. } = protect_action(action, &context());
+            options.iter().any(|option| {
+                matches!(option, AjaxRequestOption::Headers(headers) if headers.get(CSRF_HEADER) == Some(&"abc".to_string()))
+            })
+        };
+        assert!(has_header(&action("/save", "POST")));
+        assert!(has_header(&action("http://example.com/save", "DELETE")));
+        assert!(!has_header(&action("/save", "GET")));
+        assert!(!has_header(&action("https://api.other.com/save", "POST")));
+    }
+}
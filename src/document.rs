@@ -0,0 +1,147 @@
+use crate::{
+    i18n::Direction,
+    node::Node,
+    render::{HandlerMode, OutputFormat, RenderOptions, RenderState, Syntax},
+    site::Context,
+    utils::{escape_html, XHTML_NAMESPACE},
+};
+
+// the page shell that a rendered route is placed in for full page loads
+#[derive(Clone)]
+pub struct Document {
+    pub title: String,
+    pub head: Vec<Node>,
+    pub script_sources: Vec<String>,
+    pub inline_scripts: Vec<String>,
+    pub handler_mode: HandlerMode,
+    pub function_name: String,
+    pub render_options: RenderOptions,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            head: vec![],
+            script_sources: vec![],
+            inline_scripts: vec![],
+            handler_mode: HandlerMode::default(),
+            function_name: "m".to_string(),
+            render_options: RenderOptions::default(),
+        }
+    }
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn head(mut self, node: Node) -> Self {
+        self.head.push(node);
+        self
+    }
+
+    pub fn script(mut self, source: &str) -> Self {
+        self.script_sources.push(source.to_string());
+        self
+    }
+
+    // added after the body content, in order
+    pub fn inline_script(mut self, content: &str) -> Self {
+        self.inline_scripts.push(content.to_string());
+        self
+    }
+
+    // with `HandlerMode::Script` the event actions of the body are attached by one script after the inline scripts
+    pub fn handler_mode(mut self, handler_mode: HandlerMode) -> Self {
+        self.handler_mode = handler_mode;
+        self
+    }
+
+    pub fn function_name(mut self, function_name: &str) -> Self {
+        self.function_name = function_name.to_string();
+        self
+    }
+
+    pub fn render_options(mut self, render_options: RenderOptions) -> Self {
+        self.render_options = render_options;
+        self
+    }
+
+    pub fn to_html(&self, body: &Node, context: &Context) -> String {
+        let mut state = RenderState::with_options(self.render_options.clone(), self.handler_mode);
+        state.direction = context.direction;
+        let title = escape_html(&self.title);
+        let line_break = match self.render_options.format {
+            OutputFormat::Pretty => "\n",
+            OutputFormat::Compact | OutputFormat::Minified => "",
+        };
+        let head: Vec<String> = self
+            .head
+            .iter()
+            .map(|item| item.render_html(context, &mut state))
+            .collect();
+        let mut head = head.join(line_break);
+        let body = body.render_html(context, &mut state);
+        let nonce = match &context.csp_nonce {
+            Some(nonce) => format!(" nonce=\"{}\"", escape_html(nonce)),
+            None => String::new(),
+        };
+        // scoped styles of the head and the body
+        let css = state.styles_css();
+        if !css.is_empty() {
+            let style = format!("<style{nonce}>{css}</style>");
+            head = [head, style]
+                .into_iter()
+                .filter(|item| !item.is_empty())
+                .collect::<Vec<String>>()
+                .join(line_break);
+        }
+        let mut scripts: Vec<String> = self
+            .script_sources
+            .iter()
+            .map(|source| format!("<script{nonce} src=\"{}\"></script>", escape_html(source)))
+            .chain(
+                self.inline_scripts
+                    .iter()
+                    .map(|content| format!("<script{nonce}>{content}</script>")),
+            )
+            .collect();
+        let handlers_script = state.handlers_script(&self.function_name);
+        if !handlers_script.is_empty() {
+            scripts.push(format!("<script{nonce}>{handlers_script}</script>"));
+        }
+        let scripts = scripts.join(line_break);
+        // lang and dir of the whole page, elements only get them where a subtree differs
+        let mut html_attributes = String::new();
+        if let Some(lang) = &context.lang {
+            html_attributes += &format!(" lang=\"{}\"", escape_html(lang));
+        }
+        if context.lang.is_some() || context.direction == Direction::Rtl {
+            html_attributes += &format!(" dir=\"{}\"", context.direction.as_str());
+        }
+        [
+            match self.render_options.syntax {
+                Syntax::Html => format!("<!DOCTYPE html><html{html_attributes}><head><meta charset=\"utf-8\">"),
+                Syntax::Xhtml => format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><!DOCTYPE html><html xmlns=\"{XHTML_NAMESPACE}\"{html_attributes}><head><meta charset=\"utf-8\" />"),
+            },
+            format!("<title>{title}</title>"),
+            head,
+            "</head><body>".to_string(),
+            body,
+            scripts,
+            "</body></html>".to_string(),
+        ]
+        .iter()
+        .filter(|item| !item.is_empty())
+        .cloned()
+        .collect::<Vec<String>>()
+        .join(line_break)
+    }
+}
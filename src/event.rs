@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::utils::to_js_string;
+
 #[derive(Clone)]
 pub struct EventAction {
     pub hyperscript_action: HyperscriptAction,
@@ -34,72 +36,129 @@ impl HyperscriptAction {
     pub fn to_hyperscript(&self, function_name: &str) -> String {
         match self {
             Self::AjaxRequest { url, options } => {
-                // todo: escape quotes
                 let mut options_strings = vec![];
-                let mut request_method = "";
+                let mut request_method = "GET";
+                let mut form_body = false;
+                // header names aren't identifiers, e.g. `X-CSRF-Token`
+                let mut headers = HashMap::new();
                 for option in options {
                     match option {
                         AjaxRequestOption::Method(value) => {
                             request_method = value;
                         }
                         AjaxRequestOption::Params(value) => {
-                            // todo: escape quotes
-                            let mut params_strings = vec![];
-                            for (key, value) in value {
-                                params_strings.push(format!("{key}:\"{value}\""));
-                            }
-                            let params_strings = params_strings.join(",");
-                            options_strings.push("params:{".to_string() + &params_strings + "}");
+                            options_strings.push(format!("params:{}", map_to_json(value)));
                         }
                         AjaxRequestOption::Headers(value) => {
-                            // todo: escape quotes
-                            let mut params_strings = vec![];
-                            for (key, value) in value {
-                                params_strings.push(format!("{key}:\"{value}\""));
-                            }
-                            let params_strings = params_strings.join(",");
-                            options_strings.push("headers:{".to_string() + &params_strings + "}");
+                            headers.extend(value.clone());
                         }
                         AjaxRequestOption::User(value) => {
-                            options_strings.push(format!("user:\"{value}\""));
+                            options_strings.push(format!("user:{}", to_js_string(value)));
                         }
                         AjaxRequestOption::Password(value) => {
-                            options_strings.push(format!("password:\"{value}\""));
+                            options_strings.push(format!("password:{}", to_js_string(value)));
+                        }
+                        AjaxRequestOption::Body(value) if value == FORM_BODY => {
+                            form_body = true;
                         }
                         AjaxRequestOption::Body(value) => {
-                            options_strings.push(format!("body:\"{value}\""));
+                            options_strings.push(format!("body:{}", to_js_string(value)));
                         }
                         AjaxRequestOption::WithCredentials(value) => {
-                            let value_string = {
-                                if *value {
-                                    "true"
-                                } else {
-                                    "false"
-                                }
-                            };
-                            options_strings.push(format!("withCredentials:{value_string}"));
+                            options_strings.push(format!("withCredentials:{value}"));
                         }
                     }
                 }
-                if request_method == "" {
-                    request_method = "GET";
+                if form_body {
+                    headers
+                        .entry("X-Requested-With".to_string())
+                        .or_insert_with(|| "XMLHttpRequest".to_string());
+                }
+                if !headers.is_empty() {
+                    options_strings.push(format!("headers:{}", map_to_json(&headers)));
                 }
-                options_strings.push(format!("method:\"{request_method}\""));
-                let mut arguments_string = format!("url:\"{url}\"");
-                if options_strings.len() > 0 {
-                    let options_strings = options_strings.join(",");
-                    arguments_string = arguments_string + "," + &options_strings;
+                options_strings.push(format!("method:{}", to_js_string(request_method)));
+                let arguments_string =
+                    format!("url:{},{}", to_js_string(url), options_strings.join(","));
+                if form_body {
+                    return form_submit_function(function_name, &arguments_string);
                 }
                 "function(){".to_string() + function_name + ".request({" + &arguments_string + "})}"
             }
         }
     }
+
+    // interpreted by the client runtime, see `client::RUNTIME`
+    pub fn to_json(&self) -> String {
+        match self {
+            Self::AjaxRequest { url, options } => {
+                let mut items = vec![
+                    "\"action\":\"request\"".to_string(),
+                    format!("\"url\":{}", to_js_string(url)),
+                ];
+                let mut request_method = "GET";
+                for option in options {
+                    match option {
+                        AjaxRequestOption::Method(value) => request_method = value,
+                        AjaxRequestOption::Params(value) => {
+                            items.push(format!("\"params\":{}", map_to_json(value)))
+                        }
+                        AjaxRequestOption::Headers(value) => {
+                            items.push(format!("\"headers\":{}", map_to_json(value)))
+                        }
+                        AjaxRequestOption::Body(value) => {
+                            items.push(format!("\"body\":{}", to_js_string(value)))
+                        }
+                        AjaxRequestOption::User(value) => {
+                            items.push(format!("\"user\":{}", to_js_string(value)))
+                        }
+                        AjaxRequestOption::Password(value) => {
+                            items.push(format!("\"password\":{}", to_js_string(value)))
+                        }
+                        AjaxRequestOption::WithCredentials(value) => {
+                            items.push(format!("\"withCredentials\":{value}"))
+                        }
+                    }
+                }
+                items.push(format!("\"method\":{}", to_js_string(request_method)));
+                "{".to_string() + &items.join(",") + "}"
+            }
+        }
+    }
+}
+
+// submit handler of a form: posts its fields url-encoded like the browser would,
+// follows the response url on success and falls back to the regular submit otherwise,
+// so that the server renders the errors of the submission
+fn form_submit_function(function_name: &str, arguments_string: &str) -> String {
+    format!(
+        "function(e){{e.preventDefault();var form=this;\
+         {function_name}.request({{{arguments_string},\
+         body:new URLSearchParams(new FormData(form)),\
+         extract:function(xhr){{return xhr}}}})\
+         .then(function(xhr){{\
+         if(xhr.status>=200&&xhr.status<300&&{function_name}.route.get()!==undefined){{\
+         var url=new URL(xhr.responseURL);{function_name}.route.set(url.pathname+url.search)}}\
+         else if(xhr.status>=200&&xhr.status<300){{window.location.assign(xhr.responseURL)}}\
+         else{{form.submit()}}}})}}"
+    )
+}
+
+fn map_to_json(map: &HashMap<String, String>) -> String {
+    let mut items: Vec<String> = map
+        .iter()
+        .map(|(key, value)| format!("{}:{}", to_js_string(key), to_js_string(value)))
+        .collect();
+    // hashmap order is random, keep the output stable
+    items.sort();
+    "{".to_string() + &items.join(",") + "}"
 }
 
 #[derive(Clone)]
 pub enum HtmlAction {
     Redirect {
-        // behavior: nodes with this as onclick action get a parent <a href="url"> tag (only for html output)
+        // behavior: nodes with this as onclick action get a parent <a href="url"> tag (only for html output),
+        // <a> gets the href, buttons submit a get form and nothing is added inside another link
         url: String,
     },
 }
@@ -117,9 +176,67 @@ impl HtmlAction {
 pub enum AjaxRequestOption {
     Method(String),
     Params(HashMap<String, String>),
+    // `FORM_BODY` sends the fields of the submitted form, see `AjaxRequestOption::form_body`
     Body(String),
     User(String),
     Password(String),
     WithCredentials(bool),
     Headers(HashMap<String, String>),
 }
+
+// body of a form's submit action: its fields url-encoded like a regular post, see `form::Form`
+pub const FORM_BODY: &str = "forse:form-data";
+
+impl AjaxRequestOption {
+    pub fn form_body() -> Self {
+        Self::Body(FORM_BODY.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option_values_are_escaped() {
+        let action = HyperscriptAction::ajax_default(
+            "/a?b=\"c\"",
+            Some(vec![
+                AjaxRequestOption::Method("PUT".to_string()),
+                AjaxRequestOption::Body("{\"x\":\"</script>\"}".to_string()),
+                AjaxRequestOption::Params(HashMap::from([(
+                    "k\"ey".to_string(),
+                    "v\"".to_string(),
+                )])),
+                AjaxRequestOption::User("u\"".to_string()),
+                AjaxRequestOption::Password("p\\".to_string()),
+            ]),
+        );
+        assert_eq!(
+            action.to_hyperscript("m"),
+            "function(){m.request({url:\"/a?b=\\\"c\\\"\",\
+             body:\"{\\\"x\\\":\\\"\\u003c/script\\u003e\\\"}\",\
+             params:{\"k\\\"ey\":\"v\\\"\"},user:\"u\\\"\",password:\"p\\\\\",method:\"PUT\"})}"
+        );
+    }
+
+    #[test]
+    fn form_bodies_submit_the_form() {
+        let action = HyperscriptAction::ajax_default(
+            "/signup",
+            Some(vec![
+                AjaxRequestOption::Method("POST".to_string()),
+                AjaxRequestOption::form_body(),
+            ]),
+        );
+        let hyperscript = action.to_hyperscript("m");
+        assert!(hyperscript.starts_with(
+            "function(e){e.preventDefault();var form=this;m.request({url:\"/signup\",\
+             headers:{\"X-Requested-With\":\"XMLHttpRequest\"},method:\"POST\",\
+             body:new URLSearchParams(new FormData(form))"
+        ));
+        assert!(!hyperscript.contains(FORM_BODY));
+        // the client runtime compares the body with the marker
+        assert!(action.to_json().contains("\"body\":\"forse:form-data\""));
+    }
+}
@@ -0,0 +1,773 @@
+use std::fmt;
+
+use fancy_regex::Regex;
+
+use crate::{
+    aria::{AriaRole, AriaState},
+    event::{AjaxRequestOption, EventAction, HtmlAction, HyperscriptAction},
+    html::{self, ButtonType, FormMethod, HtmlElement, InputType, ParentElement},
+    node::{Node, ToNode},
+};
+
+#[cfg(feature = "derive")]
+pub use forse_web_derive::FromForm;
+
+// a form defined once for rendering, validation and parsing:
+//
+// let form = Form::new("/signup")
+//     .field(Field::input("email", InputType::Email).label("E-mail").required())
+//     .field(Field::input("name", InputType::Text).label("Name").min_length(2))
+//     .field(Field::input("age", InputType::Number).label("Age").min(18.0))
+//     .submit_label("Sign up");
+// match form.parse::<Signup>(&context.form_params()) {
+//     Ok(signup) => Response::redirect("/welcome").status(303),
+//     Err(submission) => Response::new(form.to_node_with(&submission)).status(422),
+// }
+//
+// the form is posted with ajax on the client and as a regular post without javascript,
+// see `AjaxRequestOption::form_body`
+#[derive(Clone)]
+pub struct Form {
+    pub action: String,
+    pub id: Option<String>,
+    pub fields: Vec<Field>,
+    pub submit_label: Node,
+}
+
+impl Form {
+    pub fn new(action: &str) -> Self {
+        Self {
+            action: action.to_string(),
+            id: None,
+            fields: vec![],
+            submit_label: "Submit".to_node(),
+        }
+    }
+
+    // also the prefix of the field ids, "form" by default
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn field(mut self, field: Field) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    pub fn submit_label(mut self, label: impl ToNode) -> Self {
+        self.submit_label = label.to_node();
+        self
+    }
+
+    // the submitted values of the fields with the errors of their constraints
+    pub fn validate(&self, params: &[(String, String)]) -> Submission {
+        let mut submission = Submission::default();
+        for field in &self.fields {
+            let value = params
+                .iter()
+                .find(|(key, _)| key == &field.name)
+                .map(|(_, value)| value.as_str());
+            if let Some(value) = value {
+                submission
+                    .values
+                    .push((field.name.clone(), value.to_string()));
+            }
+            for error in field.check(value.unwrap_or_default()) {
+                submission.errors.push(&field.name, error);
+            }
+        }
+        submission
+    }
+
+    // validates and converts the params, the submission with the errors of both otherwise
+    pub fn parse<T: FromForm>(&self, params: &[(String, String)]) -> Result<T, Submission> {
+        let mut submission = self.validate(params);
+        if !submission.is_valid() {
+            return Err(submission);
+        }
+        T::from_form(&submission).map_err(|errors| {
+            submission.errors = errors;
+            submission
+        })
+    }
+
+    // empty form with the initial values of the fields
+    pub fn to_node(&self) -> Node {
+        self.to_node_with(&Submission::default())
+    }
+
+    pub fn to_node_with(&self, submission: &Submission) -> Node {
+        let form_id = self.id.as_deref().unwrap_or("form");
+        // errors of the whole form, e.g. from `FromForm`, and of unknown fields
+        let form_errors: Vec<Node> = submission
+            .errors
+            .errors
+            .iter()
+            .filter(|(name, _)| !self.fields.iter().any(|field| &field.name == name))
+            .map(|(_, error)| html::p().child(error.to_string()).into_node())
+            .collect();
+        let form_errors = if form_errors.is_empty() {
+            Node::None
+        } else {
+            html::div()
+                .class("form-errors")
+                .role(AriaRole::Alert)
+                .children(form_errors)
+                .into_node()
+        };
+        let fields: Vec<Node> = self
+            .fields
+            .iter()
+            .map(|field| field.to_node(form_id, submission))
+            .collect();
+        // without javascript the browser posts the form itself
+        let submit = EventAction {
+            hyperscript_action: HyperscriptAction::ajax_default(
+                &self.action,
+                Some(vec![
+                    AjaxRequestOption::Method("POST".to_string()),
+                    AjaxRequestOption::form_body(),
+                ]),
+            ),
+            html_action: HtmlAction::redirect(&self.action),
+        };
+        let mut form = html::form()
+            .action(&self.action)
+            .method(FormMethod::Post)
+            .on("submit", submit)
+            .child(form_errors)
+            .children(fields)
+            .child(
+                html::button()
+                    .r#type(ButtonType::Submit)
+                    .child(self.submit_label.clone()),
+            );
+        if let Some(id) = &self.id {
+            form = form.id(id);
+        }
+        form.into_node()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum FieldKind {
+    // checkboxes are submitted as "on" when checked
+    Input(InputType),
+    Textarea,
+    // value and label of each option
+    Select(Vec<(String, String)>),
+}
+
+#[derive(Clone)]
+pub struct Field {
+    pub name: String,
+    pub kind: FieldKind,
+    pub label: Option<Node>,
+    // value of the empty form
+    pub initial: Option<String>,
+    pub placeholder: Option<String>,
+    pub constraints: Vec<Constraint>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Constraint {
+    Required,
+    MinLength(usize),
+    MaxLength(usize),
+    Min(f64),
+    Max(f64),
+    // same as the html attribute, the whole value has to match
+    Pattern(String),
+}
+
+impl Field {
+    pub fn new(name: &str, kind: FieldKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            label: None,
+            initial: None,
+            placeholder: None,
+            constraints: vec![],
+        }
+    }
+
+    pub fn input(name: &str, input_type: InputType) -> Self {
+        Self::new(name, FieldKind::Input(input_type))
+    }
+
+    pub fn textarea(name: &str) -> Self {
+        Self::new(name, FieldKind::Textarea)
+    }
+
+    pub fn select(name: &str, options: &[(&str, &str)]) -> Self {
+        let options = options
+            .iter()
+            .map(|(value, label)| (value.to_string(), label.to_string()))
+            .collect();
+        Self::new(name, FieldKind::Select(options))
+    }
+
+    pub fn label(mut self, label: impl ToNode) -> Self {
+        self.label = Some(label.to_node());
+        self
+    }
+
+    pub fn initial(mut self, value: &str) -> Self {
+        self.initial = Some(value.to_string());
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    pub fn required(self) -> Self {
+        self.constraint(Constraint::Required)
+    }
+
+    pub fn min_length(self, length: usize) -> Self {
+        self.constraint(Constraint::MinLength(length))
+    }
+
+    pub fn max_length(self, length: usize) -> Self {
+        self.constraint(Constraint::MaxLength(length))
+    }
+
+    pub fn min(self, value: f64) -> Self {
+        self.constraint(Constraint::Min(value))
+    }
+
+    pub fn max(self, value: f64) -> Self {
+        self.constraint(Constraint::Max(value))
+    }
+
+    pub fn pattern(self, pattern: &str) -> Self {
+        self.constraint(Constraint::Pattern(pattern.to_string()))
+    }
+
+    pub fn constraint(mut self, constraint: Constraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    fn is_required(&self) -> bool {
+        self.constraints
+            .iter()
+            .any(|item| matches!(item, Constraint::Required))
+    }
+
+    // same checks as the browser does before submitting, empty optional fields are valid
+    pub fn check(&self, value: &str) -> Vec<FieldError> {
+        if value.is_empty() {
+            return if self.is_required() {
+                vec![FieldError::Required]
+            } else {
+                vec![]
+            };
+        }
+        let mut errors = vec![];
+        match &self.kind {
+            FieldKind::Input(InputType::Email) if !is_email(value) => {
+                errors.push(FieldError::TypeMismatch)
+            }
+            FieldKind::Input(InputType::Url) if !is_absolute_url(value) => {
+                errors.push(FieldError::TypeMismatch)
+            }
+            FieldKind::Input(InputType::Number | InputType::Range)
+                if value.parse::<f64>().is_err() =>
+            {
+                errors.push(FieldError::TypeMismatch)
+            }
+            FieldKind::Select(options) if !options.iter().any(|(item, _)| item == value) => {
+                errors.push(FieldError::TypeMismatch)
+            }
+            _ => {}
+        }
+        let length = value.chars().count();
+        for constraint in &self.constraints {
+            let error = match constraint {
+                Constraint::Required => None,
+                Constraint::MinLength(min) if length < *min => Some(FieldError::TooShort(*min)),
+                Constraint::MaxLength(max) if length > *max => Some(FieldError::TooLong(*max)),
+                Constraint::Min(min) => value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|number| number < min)
+                    .map(|_| FieldError::RangeUnderflow(*min)),
+                Constraint::Max(max) => value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|number| number > max)
+                    .map(|_| FieldError::RangeOverflow(*max)),
+                Constraint::Pattern(pattern) => {
+                    let is_match = Regex::new(&format!("^(?:{pattern})$"))
+                        .ok()
+                        .and_then(|regex| regex.is_match(value).ok())
+                        .unwrap_or(false);
+                    (!is_match).then_some(FieldError::PatternMismatch)
+                }
+                Constraint::MinLength(_) | Constraint::MaxLength(_) => None,
+            };
+            errors.extend(error);
+        }
+        errors
+    }
+
+    fn to_node(&self, form_id: &str, submission: &Submission) -> Node {
+        let id = format!("{form_id}-{}", self.name);
+        let value = match (&self.kind, submission.is_empty()) {
+            // passwords aren't sent back
+            (FieldKind::Input(InputType::Password), _) => None,
+            (_, true) => self.initial.as_deref(),
+            (_, false) => submission.value(&self.name),
+        };
+        let errors = submission.errors.get(&self.name);
+
+        let control = match &self.kind {
+            FieldKind::Input(InputType::Checkbox) => {
+                let checked = value.is_some_and(|value| !value.is_empty());
+                let mut input = html::input()
+                    .r#type(InputType::Checkbox)
+                    .name(&self.name)
+                    .checked(checked);
+                input = self.with_attributes(input, &id, &errors);
+                input.into_node()
+            }
+            FieldKind::Input(input_type) => {
+                let mut input = html::input()
+                    .r#type(*input_type)
+                    .name(&self.name)
+                    .value(value.unwrap_or_default());
+                if let Some(placeholder) = &self.placeholder {
+                    input = input.placeholder(placeholder);
+                }
+                for constraint in &self.constraints {
+                    input = match constraint {
+                        Constraint::Required => input,
+                        Constraint::MinLength(length) => input.minlength(*length as u32),
+                        Constraint::MaxLength(length) => input.maxlength(*length as u32),
+                        Constraint::Min(min) => input.min(&min.to_string()),
+                        Constraint::Max(max) => input.max(&max.to_string()),
+                        Constraint::Pattern(pattern) => input.pattern(pattern),
+                    };
+                }
+                if *input_type == InputType::Hidden {
+                    return input.into_node();
+                }
+                input = self.with_attributes(input, &id, &errors);
+                input.into_node()
+            }
+            FieldKind::Textarea => {
+                let mut textarea = html::textarea()
+                    .name(&self.name)
+                    .child(value.unwrap_or_default());
+                if let Some(placeholder) = &self.placeholder {
+                    textarea = textarea.placeholder(placeholder);
+                }
+                for constraint in &self.constraints {
+                    textarea = match constraint {
+                        Constraint::MinLength(length) => textarea.minlength(*length as u32),
+                        Constraint::MaxLength(length) => textarea.maxlength(*length as u32),
+                        _ => textarea,
+                    };
+                }
+                textarea = self.with_attributes(textarea, &id, &errors);
+                textarea.into_node()
+            }
+            FieldKind::Select(options) => {
+                let options: Vec<Node> = options
+                    .iter()
+                    .map(|(option_value, label)| {
+                        html::option()
+                            .value(option_value)
+                            .selected(value == Some(option_value.as_str()))
+                            .child(label.as_str())
+                            .into_node()
+                    })
+                    .collect();
+                let select = html::select().name(&self.name).children(options);
+                self.with_attributes(select, &id, &errors).into_node()
+            }
+        };
+
+        let label = match &self.label {
+            Some(label) => html::label().r#for(&id).child(label.clone()).into_node(),
+            None => Node::None,
+        };
+        let error_message = if errors.is_empty() {
+            Node::None
+        } else {
+            let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+            html::p()
+                .class("field-error")
+                .id(&format!("{id}-error"))
+                .child(messages.join(" "))
+                .into_node()
+        };
+        let class = if errors.is_empty() {
+            "field"
+        } else {
+            "field field-invalid"
+        };
+        html::div()
+            .class(class)
+            .child(label)
+            .child(control)
+            .child(error_message)
+            .into_node()
+    }
+
+    // id, required and the error state of a control
+    fn with_attributes<T: HtmlElement>(&self, control: T, id: &str, errors: &[&FieldError]) -> T {
+        let mut control = control.id(id);
+        if self.is_required() {
+            control = control.attribute("required", true);
+        }
+        if !errors.is_empty() {
+            control = control
+                .aria(AriaState::Invalid(true))
+                .aria(AriaState::DescribedBy(format!("{id}-error")));
+        }
+        control
+    }
+}
+
+// loose check like the one of the browser, the address is only verified by sending a mail
+// a scheme and a colon, the rest isn't checked
+fn is_absolute_url(value: &str) -> bool {
+    match value.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.starts_with(|character: char| character.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || "+-.".contains(character))
+                && !value.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+fn is_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && !domain.is_empty() && !domain.contains('@') && !value.contains(' ')
+        }
+        None => false,
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum FieldError {
+    Required,
+    // e.g. not a number or an option of a select
+    TypeMismatch,
+    TooShort(usize),
+    TooLong(usize),
+    RangeUnderflow(f64),
+    RangeOverflow(f64),
+    PatternMismatch,
+    Custom(String),
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Required => write!(f, "This field is required."),
+            Self::TypeMismatch => write!(f, "Enter a valid value."),
+            Self::TooShort(min) => write!(f, "Use at least {min} characters."),
+            Self::TooLong(max) => write!(f, "Use at most {max} characters."),
+            Self::RangeUnderflow(min) => write!(f, "Enter a value of at least {min}."),
+            Self::RangeOverflow(max) => write!(f, "Enter a value of at most {max}."),
+            Self::PatternMismatch => write!(f, "Match the requested format."),
+            Self::Custom(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+// errors by field name, in order of their fields
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct FormErrors {
+    pub errors: Vec<(String, FieldError)>,
+}
+
+impl FormErrors {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // names that aren't fields of the form are shown above the fields
+    pub fn push(&mut self, name: &str, error: FieldError) {
+        self.errors.push((name.to_string(), error));
+    }
+
+    pub fn get(&self, name: &str) -> Vec<&FieldError> {
+        self.errors
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, error)| error)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn merge(&mut self, other: FormErrors) {
+        self.errors.extend(other.errors);
+    }
+}
+
+impl From<(&str, FieldError)> for FormErrors {
+    fn from((name, error): (&str, FieldError)) -> Self {
+        let mut errors = Self::new();
+        errors.push(name, error);
+        errors
+    }
+}
+
+// submitted values of the fields of a form, rendered back with their errors
+#[derive(Clone, Default, Debug)]
+pub struct Submission {
+    pub values: Vec<(String, String)>,
+    pub errors: FormErrors,
+}
+
+impl Submission {
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // typed value of a field, empty values count as missing
+    pub fn get<T: FromFormValue>(&self, name: &str) -> Result<T, FormErrors> {
+        let value = self.value(name).filter(|value| !value.is_empty());
+        T::from_form_value(value).map_err(|error| FormErrors::from((name, error)))
+    }
+
+    // e.g. a taken user name, checked after `Form::parse`
+    pub fn error(mut self, name: &str, error: FieldError) -> Self {
+        self.errors.push(name, error);
+        self
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    // nothing submitted yet
+    fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.errors.is_empty()
+    }
+}
+
+// conversion of a validated submission, `#[derive(FromForm)]` reads a field per struct field
+pub trait FromForm: Sized {
+    fn from_form(submission: &Submission) -> Result<Self, FormErrors>;
+}
+
+// `None` for missing and empty values
+pub trait FromFormValue: Sized {
+    fn from_form_value(value: Option<&str>) -> Result<Self, FieldError>;
+}
+
+impl<T: FromFormValue> FromFormValue for Option<T> {
+    fn from_form_value(value: Option<&str>) -> Result<Self, FieldError> {
+        match value {
+            Some(value) => T::from_form_value(Some(value)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+// checkboxes are only submitted when checked
+impl FromFormValue for bool {
+    fn from_form_value(value: Option<&str>) -> Result<Self, FieldError> {
+        Ok(!matches!(value, None | Some("false") | Some("off")))
+    }
+}
+
+macro_rules! from_str_form_value {
+    ($($type:ty),*) => {
+        $(
+            impl FromFormValue for $type {
+                fn from_form_value(value: Option<&str>) -> Result<Self, FieldError> {
+                    match value {
+                        Some(value) => value.trim().parse().map_err(|_| FieldError::TypeMismatch),
+                        None => Err(FieldError::Required),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_str_form_value!(
+    char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+// empty for optional fields, `Constraint::Required` rejects those before
+impl FromFormValue for String {
+    fn from_form_value(value: Option<&str>) -> Result<Self, FieldError> {
+        Ok(value.unwrap_or_default().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::Context;
+
+    fn params(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    struct Signup {
+        email: String,
+        age: u32,
+        newsletter: bool,
+    }
+
+    impl FromForm for Signup {
+        fn from_form(submission: &Submission) -> Result<Self, FormErrors> {
+            Ok(Self {
+                email: submission.get("email")?,
+                age: submission.get("age")?,
+                newsletter: submission.get("newsletter")?,
+            })
+        }
+    }
+
+    fn form() -> Form {
+        Form::new("/signup")
+            .id("signup")
+            .field(
+                Field::input("email", InputType::Email)
+                    .label("E-mail")
+                    .required(),
+            )
+            .field(
+                Field::input("age", InputType::Number)
+                    .label("Age")
+                    .min(18.0),
+            )
+            .field(Field::input("newsletter", InputType::Checkbox).label("Newsletter"))
+    }
+
+    #[test]
+    fn empty_values_are_only_invalid_when_required() {
+        let field = Field::input("name", InputType::Text).min_length(2);
+        assert_eq!(field.check(""), vec![]);
+        assert_eq!(field.check("a"), vec![FieldError::TooShort(2)]);
+        let field = field.required();
+        assert_eq!(field.check(""), vec![FieldError::Required]);
+    }
+
+    #[test]
+    fn patterns_match_the_whole_value() {
+        let field = Field::input("code", InputType::Text).pattern("[a-z]{3}|[0-9]{2}");
+        assert_eq!(field.check("abc"), vec![]);
+        assert_eq!(field.check("12"), vec![]);
+        // without the group only the first alternative would be anchored at the start
+        assert_eq!(field.check("abcd"), vec![FieldError::PatternMismatch]);
+        assert_eq!(field.check("x12"), vec![FieldError::PatternMismatch]);
+    }
+
+    #[test]
+    fn numbers_are_checked_and_parsed() {
+        let field = Field::input("age", InputType::Number).min(18.0).max(99.0);
+        assert_eq!(field.check("x"), vec![FieldError::TypeMismatch]);
+        assert_eq!(field.check("17"), vec![FieldError::RangeUnderflow(18.0)]);
+        assert_eq!(field.check("100"), vec![FieldError::RangeOverflow(99.0)]);
+        assert_eq!(u32::from_form_value(Some(" 20 ")), Ok(20));
+        assert_eq!(
+            u32::from_form_value(Some("-1")),
+            Err(FieldError::TypeMismatch)
+        );
+        assert_eq!(u32::from_form_value(None), Err(FieldError::Required));
+        assert_eq!(Option::<u32>::from_form_value(None), Ok(None));
+    }
+
+    #[test]
+    fn urls_need_a_scheme() {
+        let field = Field::input("site", InputType::Url);
+        assert_eq!(field.check("https://example.com"), vec![]);
+        assert_eq!(field.check("mailto:a@example.com"), vec![]);
+        assert_eq!(field.check("web+app:x"), vec![]);
+        assert_eq!(field.check("example.com"), vec![FieldError::TypeMismatch]);
+        // was accepted because of the `://` in the query
+        assert_eq!(
+            field.check("/a?next=https://example.com"),
+            vec![FieldError::TypeMismatch]
+        );
+        assert_eq!(field.check("1a:b"), vec![FieldError::TypeMismatch]);
+    }
+
+    #[test]
+    fn checkboxes_are_booleans() {
+        assert_eq!(bool::from_form_value(Some("on")), Ok(true));
+        assert_eq!(bool::from_form_value(Some("off")), Ok(false));
+        assert_eq!(bool::from_form_value(Some("false")), Ok(false));
+        assert_eq!(bool::from_form_value(None), Ok(false));
+    }
+
+    #[test]
+    fn parse_validates_then_converts() {
+        let signup: Signup = form()
+            .parse(&params(&[
+                ("email", "a@b.c"),
+                ("age", "20"),
+                ("newsletter", "on"),
+            ]))
+            .ok()
+            .unwrap();
+        assert_eq!(
+            (signup.email.as_str(), signup.age, signup.newsletter),
+            ("a@b.c", 20, true)
+        );
+        let submission = form()
+            .parse::<Signup>(&params(&[("email", ""), ("age", "12")]))
+            .err()
+            .unwrap();
+        assert_eq!(
+            submission.errors.errors,
+            vec![
+                ("email".to_string(), FieldError::Required),
+                ("age".to_string(), FieldError::RangeUnderflow(18.0)),
+            ]
+        );
+        // conversion errors of valid fields, the age is optional in the form but not in the struct
+        let submission = form()
+            .parse::<Signup>(&params(&[("email", "a@b.c")]))
+            .err()
+            .unwrap();
+        assert_eq!(submission.errors.get("age"), vec![&FieldError::Required]);
+    }
+
+    #[test]
+    fn errors_are_rendered_with_the_submitted_values() {
+        let context = Context::new("/");
+        let submission = form()
+            .validate(&params(&[("email", "x"), ("age", "20")]))
+            .error("", FieldError::Custom("Try again.".to_string()));
+        let html = form().to_node_with(&submission).to_html(&context);
+        assert!(html.contains("<div role=\"alert\" class=\"form-errors\"><p>Try again.</p></div>"));
+        assert!(html.contains(
+            "<div class=\"field field-invalid\"><label for=\"signup-email\">E-mail</label>\
+             <input type=\"email\" name=\"email\" value=\"x\" id=\"signup-email\" required \
+             aria-invalid=\"true\" aria-describedby=\"signup-email-error\" />\
+             <p id=\"signup-email-error\" class=\"field-error\">Enter a valid value.</p></div>"
+        ));
+        assert!(html.contains(
+            "<div class=\"field\"><label for=\"signup-age\">Age</label>\
+             <input type=\"number\" name=\"age\" value=\"20\" min=\"18\" id=\"signup-age\" /></div>"
+        ));
+    }
+}
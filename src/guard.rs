@@ -0,0 +1,75 @@
+// guards return the same `Response` as route callbacks, it's moved once per request
+#![allow(clippy::result_large_err)]
+
+use std::rc::Rc;
+
+use crate::{response::Response, router_view::RouteParams, site::Context, utils::to_js_string};
+
+pub type GuardCallback = Rc<dyn Fn(&Context, &RouteParams) -> Result<(), Response>>;
+
+// runs before a route's content callback, an Err response is returned instead of the route content
+#[derive(Clone)]
+pub struct Guard {
+    pub name: String,
+    pub check: GuardCallback,
+    pub client_check: Option<ClientGuardCheck>,
+}
+
+// javascript counterpart of a guard, used in the `onmatch` of the generated mithril routes
+#[derive(Clone)]
+pub struct ClientGuardCheck {
+    // expression that evaluates to true when the route can be entered, `args` and `requestedPath` are in scope
+    pub condition: String,
+    pub redirect_path: String,
+}
+
+impl Guard {
+    pub fn new(
+        name: &str,
+        check: impl Fn(&Context, &RouteParams) -> Result<(), Response> + 'static,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            check: Rc::new(check),
+            client_check: None,
+        }
+    }
+
+    pub fn redirect_unless(
+        name: &str,
+        redirect_path: &str,
+        is_allowed: impl Fn(&Context, &RouteParams) -> bool + 'static,
+    ) -> Self {
+        let redirect_path = redirect_path.to_string();
+        Self::new(name, move |context, params| {
+            if is_allowed(context, params) {
+                Ok(())
+            } else {
+                Err(Response::redirect(&redirect_path))
+            }
+        })
+    }
+
+    pub fn client(mut self, condition: &str, redirect_path: &str) -> Self {
+        self.client_check = Some(ClientGuardCheck {
+            condition: condition.to_string(),
+            redirect_path: redirect_path.to_string(),
+        });
+        self
+    }
+
+    pub fn run(&self, context: &Context, params: &RouteParams) -> Result<(), Response> {
+        (self.check)(context, params)
+    }
+
+    pub fn to_hyperscript(&self, function_name: &str) -> String {
+        if let Some(client_check) = &self.client_check {
+            let condition = &client_check.condition;
+            let redirect_path = to_js_string(&client_check.redirect_path);
+            // the pending promise is dropped by mithril once the redirect resolves
+            format!("if(!({condition})){{{function_name}.route.set({redirect_path});return new Promise(function(){{}})}}")
+        } else {
+            String::new()
+        }
+    }
+}
@@ -0,0 +1,428 @@
+// typed builders for html elements, each element only has methods for the attributes it accepts
+// and void elements have no children methods, e.g. `a().href("/").child("home").into_node()`
+use crate::{
+    aria::{AriaRole, AriaState},
+    attribute::{self, Attribute, AttributeValue, ToAttributeEntries, ToAttributeValue},
+    class_list::ClassList,
+    event::EventAction,
+    node::{Node, TagNode, ToNode},
+    style::StyleSheet,
+    utils::parse_elem_properties,
+};
+
+pub struct ElementData {
+    pub tag: &'static str,
+    pub attributes: Vec<Attribute>,
+    pub children: Vec<Node>,
+}
+
+impl ElementData {
+    pub fn new(tag: &'static str) -> Self {
+        Self {
+            tag,
+            attributes: vec![],
+            children: vec![],
+        }
+    }
+
+    // class attributes are merged the same way as in `elem`
+    pub fn into_node(self) -> Node {
+        let properties = parse_elem_properties(self.tag, &Some(self.attributes));
+        Node::Tag(TagNode {
+            properties: Some(properties),
+            children: self.children,
+        })
+    }
+}
+
+// global attributes, available on every element
+pub trait HtmlElement: Sized {
+    fn data_mut(&mut self) -> &mut ElementData;
+
+    fn into_data(self) -> ElementData;
+
+    fn into_node(self) -> Node {
+        self.into_data().into_node()
+    }
+
+    fn attribute(mut self, key: &str, value: impl ToAttributeValue) -> Self {
+        self.data_mut().attributes.push(Attribute::from(key, value));
+        self
+    }
+
+    fn id(self, value: &str) -> Self {
+        self.attribute("id", value)
+    }
+
+    fn class(self, value: &str) -> Self {
+        self.attribute("class", value)
+    }
+
+    // merged with the other classes
+    fn scoped_class(self, style: &StyleSheet) -> Self {
+        self.attribute("class", style)
+    }
+
+    fn class_list(self, list: ClassList) -> Self {
+        self.attribute("class", list)
+    }
+
+    // `data-*` attributes, see `attribute::data`
+    fn dataset(mut self, entries: &(impl ToAttributeEntries + ?Sized)) -> Self {
+        self.data_mut().attributes.extend(attribute::data(entries));
+        self
+    }
+
+    fn role(self, role: AriaRole) -> Self {
+        self.attribute("role", role)
+    }
+
+    fn aria(mut self, state: AriaState) -> Self {
+        self.data_mut().attributes.push(state.to_attribute());
+        self
+    }
+
+    fn title(self, value: &str) -> Self {
+        self.attribute("title", value)
+    }
+
+    fn lang(self, value: &str) -> Self {
+        self.attribute("lang", value)
+    }
+
+    fn dir(self, value: Dir) -> Self {
+        self.attribute("dir", value)
+    }
+
+    fn style(self, value: &str) -> Self {
+        self.attribute("style", value)
+    }
+
+    fn hidden(self, value: bool) -> Self {
+        self.attribute("hidden", value)
+    }
+
+    fn tabindex(self, value: i32) -> Self {
+        self.attribute("tabindex", value)
+    }
+
+    fn accesskey(self, value: &str) -> Self {
+        self.attribute("accesskey", value)
+    }
+
+    fn autofocus(self, value: bool) -> Self {
+        self.attribute("autofocus", value)
+    }
+
+    // e.g. `on("click", ...)` for `onclick`
+    fn on(self, event: &str, action: EventAction) -> Self {
+        self.attribute(&("on".to_string() + event), action)
+    }
+}
+
+// elements that can have children, void elements don't implement this
+pub trait ParentElement: HtmlElement {
+    fn child(mut self, child: impl ToNode) -> Self {
+        self.data_mut().children.push(child.to_node());
+        self
+    }
+
+    fn children<T: ToNode>(mut self, children: impl IntoIterator<Item = T>) -> Self {
+        let children = children.into_iter().map(|child| child.to_node());
+        self.data_mut().children.extend(children);
+        self
+    }
+}
+
+macro_rules! attribute_enum {
+    ($name:ident { $($variant:ident => $value:literal),* $(,)? }) => {
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl $name {
+            pub const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $value),*
+                }
+            }
+        }
+
+        impl ToAttributeValue for $name {
+            fn to_attribute_value(&self) -> AttributeValue {
+                AttributeValue::String(self.as_str().to_string())
+            }
+        }
+    };
+}
+
+// also used for the aria enums
+pub(crate) use attribute_enum;
+
+attribute_enum!(Dir { Ltr => "ltr", Rtl => "rtl", Auto => "auto" });
+
+attribute_enum!(InputType {
+    Button => "button",
+    Checkbox => "checkbox",
+    Color => "color",
+    Date => "date",
+    DatetimeLocal => "datetime-local",
+    Email => "email",
+    File => "file",
+    Hidden => "hidden",
+    Image => "image",
+    Month => "month",
+    Number => "number",
+    Password => "password",
+    Radio => "radio",
+    Range => "range",
+    Reset => "reset",
+    Search => "search",
+    Submit => "submit",
+    Tel => "tel",
+    Text => "text",
+    Time => "time",
+    Url => "url",
+    Week => "week",
+});
+
+attribute_enum!(ButtonType { Button => "button", Submit => "submit", Reset => "reset" });
+
+attribute_enum!(FormMethod { Get => "get", Post => "post", Dialog => "dialog" });
+
+attribute_enum!(Target { Self_ => "_self", Blank => "_blank", Parent => "_parent", Top => "_top" });
+
+attribute_enum!(Loading { Eager => "eager", Lazy => "lazy" });
+
+attribute_enum!(Preload { None => "none", Metadata => "metadata", Auto => "auto" });
+
+attribute_enum!(Scope { Row => "row", Col => "col", RowGroup => "rowgroup", ColGroup => "colgroup" });
+
+attribute_enum!(CrossOrigin { Anonymous => "anonymous", UseCredentials => "use-credentials" });
+
+macro_rules! element {
+    ($function:ident, $name:ident, $tag:literal, [$($attribute:ident($key:literal): $value:ty),* $(,)?]) => {
+        pub struct $name(ElementData);
+
+        pub fn $function() -> $name {
+            $name(ElementData::new($tag))
+        }
+
+        impl HtmlElement for $name {
+            fn data_mut(&mut self) -> &mut ElementData {
+                &mut self.0
+            }
+
+            fn into_data(self) -> ElementData {
+                self.0
+            }
+        }
+
+        impl $name {
+            $(
+                pub fn $attribute(self, value: $value) -> Self {
+                    self.attribute($key, value)
+                }
+            )*
+        }
+
+        impl ToNode for $name {
+            fn to_node(&self) -> Node {
+                let data = ElementData {
+                    tag: self.0.tag,
+                    attributes: self.0.attributes.clone(),
+                    children: self.0.children.clone(),
+                };
+                data.into_node()
+            }
+        }
+    };
+}
+
+macro_rules! elements {
+    (
+        void { $($void_function:ident $void_name:ident $void_tag:literal [$($void_attributes:tt)*];)* }
+        normal { $($function:ident $name:ident $tag:literal [$($attributes:tt)*];)* }
+    ) => {
+        $(element!($void_function, $void_name, $void_tag, [$($void_attributes)*]);)*
+        $(
+            element!($function, $name, $tag, [$($attributes)*]);
+            impl ParentElement for $name {}
+        )*
+    };
+}
+
+// https://html.spec.whatwg.org/multipage/indices.html#elements-3
+elements! {
+    void {
+        area Area "area" [alt("alt"): &str, coords("coords"): &str, shape("shape"): &str, href("href"): &str, target("target"): Target, download("download"): &str, rel("rel"): &str];
+        base Base "base" [href("href"): &str, target("target"): Target];
+        br Br "br" [];
+        col Col "col" [span("span"): u32];
+        embed Embed "embed" [src("src"): &str, r#type("type"): &str, width("width"): u32, height("height"): u32];
+        hr Hr "hr" [];
+        img Img "img" [alt("alt"): &str, src("src"): &str, srcset("srcset"): &str, sizes("sizes"): &str, width("width"): u32, height("height"): u32, loading("loading"): Loading, crossorigin("crossorigin"): CrossOrigin, usemap("usemap"): &str, ismap("ismap"): bool];
+        input Input "input" [
+            r#type("type"): InputType, name("name"): &str, value("value"): &str, placeholder("placeholder"): &str,
+            checked("checked"): bool, disabled("disabled"): bool, readonly("readonly"): bool, required("required"): bool,
+            multiple("multiple"): bool, min("min"): &str, max("max"): &str, step("step"): &str,
+            minlength("minlength"): u32, maxlength("maxlength"): u32, pattern("pattern"): &str, size("size"): u32,
+            accept("accept"): &str, autocomplete("autocomplete"): &str, form("form"): &str, list("list"): &str,
+            alt("alt"): &str, src("src"): &str, width("width"): u32, height("height"): u32
+        ];
+        link Link "link" [href("href"): &str, rel("rel"): &str, r#type("type"): &str, media("media"): &str, hreflang("hreflang"): &str, sizes("sizes"): &str, crossorigin("crossorigin"): CrossOrigin, integrity("integrity"): &str, r#as("as"): &str];
+        meta Meta "meta" [name("name"): &str, content("content"): &str, charset("charset"): &str, http_equiv("http-equiv"): &str];
+        source Source "source" [src("src"): &str, srcset("srcset"): &str, sizes("sizes"): &str, r#type("type"): &str, media("media"): &str];
+        track Track "track" [src("src"): &str, kind("kind"): &str, srclang("srclang"): &str, label("label"): &str, default("default"): bool];
+        wbr Wbr "wbr" [];
+    }
+    normal {
+        a A "a" [href("href"): &str, target("target"): Target, rel("rel"): &str, download("download"): &str, hreflang("hreflang"): &str, r#type("type"): &str, referrerpolicy("referrerpolicy"): &str];
+        abbr Abbr "abbr" [];
+        address Address "address" [];
+        article Article "article" [];
+        aside Aside "aside" [];
+        audio Audio "audio" [src("src"): &str, controls("controls"): bool, autoplay("autoplay"): bool, r#loop("loop"): bool, muted("muted"): bool, preload("preload"): Preload, crossorigin("crossorigin"): CrossOrigin];
+        b B "b" [];
+        blockquote Blockquote "blockquote" [cite("cite"): &str];
+        body Body "body" [];
+        button Button "button" [r#type("type"): ButtonType, name("name"): &str, value("value"): &str, disabled("disabled"): bool, form("form"): &str, formaction("formaction"): &str, formmethod("formmethod"): FormMethod];
+        canvas Canvas "canvas" [width("width"): u32, height("height"): u32];
+        caption Caption "caption" [];
+        cite Cite "cite" [];
+        code Code "code" [];
+        colgroup Colgroup "colgroup" [span("span"): u32];
+        data Data "data" [value("value"): &str];
+        datalist Datalist "datalist" [];
+        dd Dd "dd" [];
+        del Del "del" [cite("cite"): &str, datetime("datetime"): &str];
+        details Details "details" [open("open"): bool];
+        dfn Dfn "dfn" [];
+        dialog Dialog "dialog" [open("open"): bool];
+        div Div "div" [];
+        dl Dl "dl" [];
+        dt Dt "dt" [];
+        em Em "em" [];
+        fieldset Fieldset "fieldset" [disabled("disabled"): bool, form("form"): &str, name("name"): &str];
+        figcaption Figcaption "figcaption" [];
+        figure Figure "figure" [];
+        footer Footer "footer" [];
+        form Form "form" [action("action"): &str, method("method"): FormMethod, enctype("enctype"): &str, target("target"): Target, novalidate("novalidate"): bool, autocomplete("autocomplete"): &str, name("name"): &str];
+        h1 H1 "h1" [];
+        h2 H2 "h2" [];
+        h3 H3 "h3" [];
+        h4 H4 "h4" [];
+        h5 H5 "h5" [];
+        h6 H6 "h6" [];
+        head Head "head" [];
+        header Header "header" [];
+        html Html "html" [];
+        i I "i" [];
+        iframe Iframe "iframe" [src("src"): &str, srcdoc("srcdoc"): &str, name("name"): &str, width("width"): u32, height("height"): u32, sandbox("sandbox"): &str, allow("allow"): &str, loading("loading"): Loading, referrerpolicy("referrerpolicy"): &str];
+        ins Ins "ins" [cite("cite"): &str, datetime("datetime"): &str];
+        kbd Kbd "kbd" [];
+        label Label "label" [r#for("for"): &str];
+        legend Legend "legend" [];
+        li Li "li" [value("value"): i32];
+        main Main "main" [];
+        map Map "map" [name("name"): &str];
+        mark Mark "mark" [];
+        menu Menu "menu" [];
+        meter Meter "meter" [value("value"): f64, min("min"): f64, max("max"): f64, low("low"): f64, high("high"): f64, optimum("optimum"): f64];
+        nav Nav "nav" [];
+        noscript Noscript "noscript" [];
+        object Object "object" [data("data"): &str, r#type("type"): &str, name("name"): &str, width("width"): u32, height("height"): u32];
+        ol Ol "ol" [reversed("reversed"): bool, start("start"): i32, r#type("type"): &str];
+        optgroup Optgroup "optgroup" [label("label"): &str, disabled("disabled"): bool];
+        option Option_ "option" [value("value"): &str, label("label"): &str, selected("selected"): bool, disabled("disabled"): bool];
+        output Output "output" [r#for("for"): &str, form("form"): &str, name("name"): &str];
+        p P "p" [];
+        picture Picture "picture" [];
+        pre Pre "pre" [];
+        progress Progress "progress" [value("value"): f64, max("max"): f64];
+        q Q "q" [cite("cite"): &str];
+        s S "s" [];
+        samp Samp "samp" [];
+        script Script "script" [src("src"): &str, r#type("type"): &str, r#async("async"): bool, defer("defer"): bool, nomodule("nomodule"): bool, crossorigin("crossorigin"): CrossOrigin, integrity("integrity"): &str, nonce("nonce"): &str];
+        search Search "search" [];
+        section Section "section" [];
+        select Select "select" [name("name"): &str, multiple("multiple"): bool, disabled("disabled"): bool, required("required"): bool, size("size"): u32, form("form"): &str, autocomplete("autocomplete"): &str];
+        slot Slot "slot" [name("name"): &str];
+        small Small "small" [];
+        span Span "span" [];
+        strong Strong "strong" [];
+        style Style "style" [media("media"): &str, nonce("nonce"): &str];
+        sub Sub "sub" [];
+        summary Summary "summary" [];
+        sup Sup "sup" [];
+        table Table "table" [];
+        tbody Tbody "tbody" [];
+        td Td "td" [colspan("colspan"): u32, rowspan("rowspan"): u32, headers("headers"): &str];
+        template Template "template" [];
+        textarea Textarea "textarea" [name("name"): &str, rows("rows"): u32, cols("cols"): u32, placeholder("placeholder"): &str, disabled("disabled"): bool, readonly("readonly"): bool, required("required"): bool, minlength("minlength"): u32, maxlength("maxlength"): u32, wrap("wrap"): &str, form("form"): &str, autocomplete("autocomplete"): &str];
+        tfoot Tfoot "tfoot" [];
+        th Th "th" [colspan("colspan"): u32, rowspan("rowspan"): u32, headers("headers"): &str, scope("scope"): Scope, abbr("abbr"): &str];
+        thead Thead "thead" [];
+        time Time "time" [datetime("datetime"): &str];
+        title Title "title" [];
+        tr Tr "tr" [];
+        u U "u" [];
+        ul Ul "ul" [];
+        var Var "var" [];
+        video Video "video" [src("src"): &str, poster("poster"): &str, width("width"): u32, height("height"): u32, controls("controls"): bool, autoplay("autoplay"): bool, r#loop("loop"): bool, muted("muted"): bool, playsinline("playsinline"): bool, preload("preload"): Preload, crossorigin("crossorigin"): CrossOrigin];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::Context;
+
+    #[test]
+    fn builders_set_typed_attributes_and_children() {
+        let context = Context::new("/");
+        let link = a()
+            .href("/docs")
+            .target(Target::Blank)
+            .id("docs")
+            .child("Docs")
+            .into_node();
+        assert_eq!(
+            link.to_html(&context),
+            "<a href=\"/docs\" target=\"_blank\" id=\"docs\">Docs</a>"
+        );
+        let list = ul().children(["a", "b"].map(|item| li().child(item)));
+        assert_eq!(
+            list.to_node().to_html(&context),
+            "<ul><li>a</li><li>b</li></ul>"
+        );
+    }
+
+    #[test]
+    fn void_elements_render_without_children() {
+        let context = Context::new("/");
+        let field = input()
+            .r#type(InputType::DatetimeLocal)
+            .name("at")
+            .required(true)
+            .disabled(false)
+            .into_node();
+        assert_eq!(
+            field.to_html(&context),
+            "<input type=\"datetime-local\" name=\"at\" required />"
+        );
+    }
+
+    #[test]
+    fn attribute_enums_are_their_html_values() {
+        assert_eq!(Target::Self_.as_str(), "_self");
+        assert_eq!(InputType::DatetimeLocal.as_str(), "datetime-local");
+        assert!(matches!(
+            Dir::Rtl.to_attribute_value(),
+            AttributeValue::String(value) if value == "rtl"
+        ));
+    }
+}
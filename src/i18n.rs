@@ -0,0 +1,764 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    node::{Node, ToNode},
+    site::Context,
+};
+
+// message catalogs in a subset of the fluent syntax (https://projectfluent.org/fluent/guide/):
+// `key = text { $variable } { -term } { other-message }`, indented continuation lines,
+// `.attribute = ...` lines and select expressions with one variant per line:
+//
+// emails = { $count ->
+//     [0] No emails
+//     [one] One email
+//    *[other] { $count } emails
+// }
+#[derive(Clone, Debug)]
+pub struct Catalog {
+    pub locale: String,
+    messages: HashMap<String, Pattern>,
+}
+
+type Pattern = Vec<PatternElement>;
+
+#[derive(Clone, Debug)]
+enum PatternElement {
+    Text(String),
+    Variable(String),
+    // messages, terms (`-name`) and attributes (`name.attribute`)
+    Reference(String),
+    Select {
+        variable: String,
+        variants: Vec<(String, Pattern)>,
+        default: usize,
+    },
+}
+
+// a message referencing itself would loop forever
+const MAX_REFERENCE_DEPTH: usize = 8;
+
+impl Catalog {
+    pub fn parse(locale: &str, source: &str) -> Result<Self, String> {
+        let mut messages = HashMap::new();
+        // (line number, key, value lines)
+        let mut entries: Vec<(usize, String, Vec<String>)> = vec![];
+        for (index, line) in source.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || (trimmed.starts_with('#') && !line.starts_with(' ')) {
+                continue;
+            }
+            // closing braces and variants don't need to be indented
+            let is_continuation = line.starts_with([' ', '\t', '}', '[', '*']);
+            if !is_continuation {
+                match parse_entry_line(trimmed, '-') {
+                    Some((key, value)) => entries.push((index + 1, key, vec![value])),
+                    None => return Err(format!("Line {}: expected `key = value`", index + 1)),
+                }
+                continue;
+            }
+            let Some((_, key, lines)) = entries.last_mut() else {
+                return Err(format!(
+                    "Line {}: indented line outside of a message",
+                    index + 1
+                ));
+            };
+            match parse_entry_line(trimmed, '.') {
+                Some((attribute, value)) => {
+                    let key = format!("{}.{attribute}", key.split('.').next().unwrap_or_default());
+                    entries.push((index + 1, key, vec![value]));
+                }
+                None => lines.push(trimmed.to_string()),
+            }
+        }
+        for (line, key, lines) in entries {
+            let value: Vec<String> = lines.into_iter().filter(|item| !item.is_empty()).collect();
+            let pattern = Parser::new(&value.join("\n"))
+                .parse_pattern(false)
+                .map_err(|error| format!("Line {line}: {error} in \"{key}\""))?;
+            messages.insert(key, pattern);
+        }
+        Ok(Self {
+            locale: locale.to_string(),
+            messages,
+        })
+    }
+
+    pub fn load(locale: &str, path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|error| format!("Couldn't read {}: {error}", path.display()))?;
+        Self::parse(locale, &source)
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        self.messages.contains_key(key)
+    }
+
+    pub fn format(&self, key: &str, args: &[(String, String)]) -> Option<String> {
+        self.format_reference(key, args, 0)
+    }
+
+    fn format_reference(
+        &self,
+        key: &str,
+        args: &[(String, String)],
+        depth: usize,
+    ) -> Option<String> {
+        if depth > MAX_REFERENCE_DEPTH {
+            return None;
+        }
+        let pattern = self.messages.get(key)?;
+        Some(self.format_pattern(pattern, args, depth))
+    }
+
+    fn format_pattern(&self, pattern: &Pattern, args: &[(String, String)], depth: usize) -> String {
+        let mut result = String::new();
+        for element in pattern {
+            match element {
+                PatternElement::Text(text) => result.push_str(text),
+                PatternElement::Variable(name) => match argument(args, name) {
+                    Some(value) => result.push_str(value),
+                    None => result.push_str(&format!("{{${name}}}")),
+                },
+                PatternElement::Reference(key) => {
+                    match self.format_reference(key, args, depth + 1) {
+                        Some(value) => result.push_str(&value),
+                        None => result.push_str(&format!("{{{key}}}")),
+                    }
+                }
+                PatternElement::Select {
+                    variable,
+                    variants,
+                    default,
+                } => {
+                    let index = argument(args, variable)
+                        .and_then(|value| self.select_variant(variants, value))
+                        .unwrap_or(*default);
+                    result.push_str(&self.format_pattern(&variants[index].1, args, depth));
+                }
+            }
+        }
+        result
+    }
+
+    // exact matches win over plural categories, e.g. `[0]` before `[other]`
+    fn select_variant(&self, variants: &[(String, Pattern)], value: &str) -> Option<usize> {
+        let number = value.parse::<f64>().ok();
+        variants
+            .iter()
+            .position(|(key, _)| {
+                key == value
+                    || matches!((number, key.parse::<f64>()), (Some(number), Ok(key)) if number == key)
+            })
+            .or_else(|| {
+                let category = plural_category(&self.locale, number?);
+                variants.iter().position(|(key, _)| key == category)
+            })
+    }
+}
+
+// `key = value` with keys starting with a letter, `prefix` allows the `-` of terms and the `.` of attributes
+fn parse_entry_line(line: &str, prefix: char) -> Option<(String, String)> {
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim();
+    let name = key.strip_prefix(prefix).unwrap_or(key);
+    let is_valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !is_valid || (prefix == '.' && name == key) {
+        return None;
+    }
+    let key = if prefix == '.' { name } else { key };
+    Some((key.to_string(), value.trim().to_string()))
+}
+
+fn argument<'a>(args: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    args.iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(source: &str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self, newlines: bool) {
+        while let Some(c) = self.peek() {
+            if c == ' ' || c == '\t' || (newlines && c == '\n') {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(c) => Err(format!("expected '{expected}', found '{c}'")),
+            None => Err(format!("expected '{expected}'")),
+        }
+    }
+
+    // variants end at the end of their line
+    fn parse_pattern(&mut self, is_variant: bool) -> Result<Pattern, String> {
+        let mut pattern = vec![];
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '{' => {
+                    if !text.is_empty() {
+                        pattern.push(PatternElement::Text(std::mem::take(&mut text)));
+                    }
+                    pattern.push(self.parse_placeable()?);
+                }
+                '}' if is_variant => break,
+                '}' => return Err("unexpected '}'".to_string()),
+                '\n' if is_variant => break,
+                _ => {
+                    text.push(c);
+                    self.position += 1;
+                }
+            }
+        }
+        if is_variant {
+            text = text.trim_end().to_string();
+        }
+        if !text.is_empty() {
+            pattern.push(PatternElement::Text(text));
+        }
+        Ok(pattern)
+    }
+
+    fn parse_placeable(&mut self) -> Result<PatternElement, String> {
+        self.expect('{')?;
+        self.skip_whitespace(true);
+        let element = match self.peek() {
+            Some('$') => {
+                self.position += 1;
+                let name = self.parse_identifier()?;
+                self.skip_whitespace(true);
+                if self.chars[self.position..].starts_with(&['-', '>']) {
+                    self.position += 2;
+                    return self.parse_variants(name);
+                }
+                PatternElement::Variable(name)
+            }
+            Some('"') => {
+                self.position += 1;
+                let mut literal = String::new();
+                loop {
+                    match self.peek() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            self.position += 1;
+                            literal.extend(self.peek());
+                        }
+                        Some(c) => literal.push(c),
+                        None => return Err("unterminated string literal".to_string()),
+                    }
+                    self.position += 1;
+                }
+                self.position += 1;
+                PatternElement::Text(literal)
+            }
+            Some('-') => {
+                self.position += 1;
+                PatternElement::Reference(format!("-{}", self.parse_identifier()?))
+            }
+            _ => PatternElement::Reference(self.parse_identifier()?),
+        };
+        self.skip_whitespace(true);
+        self.expect('}')?;
+        Ok(element)
+    }
+
+    fn parse_variants(&mut self, variable: String) -> Result<PatternElement, String> {
+        let mut variants = vec![];
+        let mut default = None;
+        loop {
+            self.skip_whitespace(true);
+            match self.peek() {
+                Some('}') => {
+                    self.position += 1;
+                    break;
+                }
+                Some('*') => {
+                    self.position += 1;
+                    default = Some(variants.len());
+                }
+                Some(_) => {}
+                None => return Err("unterminated select expression".to_string()),
+            }
+            self.expect('[')?;
+            let mut key = String::new();
+            while let Some(c) = self.peek().filter(|c| *c != ']' && *c != '\n') {
+                key.push(c);
+                self.position += 1;
+            }
+            self.expect(']')?;
+            self.skip_whitespace(false);
+            let pattern = self.parse_pattern(true)?;
+            variants.push((key.trim().to_string(), pattern));
+        }
+        let default =
+            default.ok_or_else(|| "select expression without a *[default] variant".to_string())?;
+        Ok(PatternElement::Select {
+            variable,
+            variants,
+            default,
+        })
+    }
+
+    fn parse_identifier(&mut self) -> Result<String, String> {
+        let mut identifier = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_ascii_alphanumeric() || ['-', '_', '.'].contains(c))
+        {
+            identifier.push(c);
+            self.position += 1;
+        }
+        if identifier.is_empty() {
+            return Err("expected an identifier".to_string());
+        }
+        Ok(identifier)
+    }
+}
+
+// cldr plural categories of integers and decimals for the common languages, others use one/other
+// https://www.unicode.org/cldr/charts/latest/supplemental/language_plural_rules.html
+pub fn plural_category(locale: &str, number: f64) -> &'static str {
+    let language = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let is_integer = number.fract() == 0.0;
+    let integer = number.abs().trunc() as u64;
+    let (last, last_two) = (integer % 10, integer % 100);
+    // the visible fraction digits, e.g. 21 for 1.21
+    let fraction = number
+        .abs()
+        .to_string()
+        .split_once('.')
+        .and_then(|(_, digits)| digits.parse::<u64>().ok())
+        .unwrap_or(0);
+    let (fraction_last, fraction_last_two) = (fraction % 10, fraction % 100);
+    match language.as_str() {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" | "lo" | "my" => "other",
+        "fr" | "hi" | "fa" | "bn" if integer <= 1 => "one",
+        "fr" | "hi" | "fa" | "bn" => "other",
+        "hr" | "sr" | "bs" => {
+            let (last, last_two) = if is_integer {
+                (last, last_two)
+            } else {
+                (fraction_last, fraction_last_two)
+            };
+            if last == 1 && last_two != 11 {
+                "one"
+            } else if (2..=4).contains(&last) && !(12..=14).contains(&last_two) {
+                "few"
+            } else {
+                "other"
+            }
+        }
+        "ru" | "uk" | "be" if !is_integer => "other",
+        "ru" | "uk" | "be" => {
+            if last == 1 && last_two != 11 {
+                "one"
+            } else if (2..=4).contains(&last) && !(12..=14).contains(&last_two) {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "pl" if !is_integer => "other",
+        "pl" if integer == 1 => "one",
+        "pl" if (2..=4).contains(&last) && !(12..=14).contains(&last_two) => "few",
+        "pl" => "many",
+        "cs" | "sk" if !is_integer => "many",
+        "cs" | "sk" if integer == 1 => "one",
+        "cs" | "sk" if (2..=4).contains(&integer) => "few",
+        "cs" | "sk" => "other",
+        "ar" if !is_integer => "other",
+        "ar" => match (integer, last_two) {
+            (0, _) => "zero",
+            (1, _) => "one",
+            (2, _) => "two",
+            (_, 3..=10) => "few",
+            (_, 11..=99) => "many",
+            _ => "other",
+        },
+        _ if is_integer && integer == 1 => "one",
+        _ => "other",
+    }
+}
+
+// the catalogs of a site, shared between requests as a `Context` extension:
+// `Context::builder(path).extension(Rc::new(translations))`
+#[derive(Clone, Debug)]
+pub struct Translations {
+    pub default_locale: String,
+    catalogs: Vec<Catalog>,
+}
+
+impl Translations {
+    pub fn new(default_locale: &str) -> Self {
+        Self {
+            default_locale: default_locale.to_string(),
+            catalogs: vec![],
+        }
+    }
+
+    pub fn catalog(mut self, catalog: Catalog) -> Self {
+        self.catalogs.push(catalog);
+        self
+    }
+
+    // every `<locale>.ftl` file of the directory, e.g. `en-US.ftl`
+    pub fn load_dir(mut self, dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref();
+        let entries = fs::read_dir(dir)
+            .map_err(|error| format!("Couldn't read {}: {error}", dir.display()))?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "ftl"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            if let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) {
+                self.catalogs.push(Catalog::load(locale, &path)?);
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn locales(&self) -> Vec<&str> {
+        self.catalogs
+            .iter()
+            .map(|catalog| catalog.locale.as_str())
+            .collect()
+    }
+
+    // exact locale matches first, then the language ("de-AT" finds "de" or "de-DE"), then the default locale
+    pub fn negotiate(&self, preferred: &[String]) -> Vec<&Catalog> {
+        let language = |locale: &str| {
+            locale
+                .split(['-', '_'])
+                .next()
+                .unwrap_or_default()
+                .to_lowercase()
+        };
+        let mut result: Vec<&Catalog> = vec![];
+        let locales = preferred.iter().map(|item| item.as_str());
+        for locale in locales.chain([self.default_locale.as_str()]) {
+            let exact = self
+                .catalogs
+                .iter()
+                .filter(|catalog| catalog.locale.eq_ignore_ascii_case(locale));
+            let same_language = self
+                .catalogs
+                .iter()
+                .filter(|catalog| language(&catalog.locale) == language(locale));
+            for catalog in exact.chain(same_language) {
+                if !result.iter().any(|item| item.locale == catalog.locale) {
+                    result.push(catalog);
+                }
+            }
+        }
+        result
+    }
+
+    // missing messages fall back to the next locale and finally to the key itself
+    pub fn translate(&self, preferred: &[String], key: &str, args: &[(String, String)]) -> String {
+        self.negotiate(preferred)
+            .iter()
+            .find(|catalog| catalog.has(key))
+            .and_then(|catalog| catalog.format(key, args))
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+// text resolved against the `Translations` of the context when rendered
+#[derive(Clone, Debug)]
+pub struct TranslationNode {
+    pub key: String,
+    pub args: Vec<(String, String)>,
+}
+
+impl TranslationNode {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            args: vec![],
+        }
+    }
+
+    // numbers select the plural variants
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.args.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn resolve(&self, context: &Context) -> String {
+        context.translate(&self.key, &self.args)
+    }
+}
+
+impl ToNode for TranslationNode {
+    fn to_node(&self) -> Node {
+        Node::Translation(self.clone())
+    }
+}
+
+// languages written right to left, script subtags like "az-Arab" are checked separately
+pub const RTL_LANGUAGES: &[&str] = &[
+    "ar", "arc", "ckb", "dv", "fa", "he", "iw", "ks", "ps", "sd", "syr", "ug", "ur", "yi",
+];
+
+const RTL_SCRIPTS: &[&str] = &["arab", "hebr", "thaa", "syrc", "nkoo", "adlm", "rohg"];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl Direction {
+    pub fn of_locale(locale: &str) -> Self {
+        let mut subtags = locale.split(['-', '_']).map(|item| item.to_lowercase());
+        let language = subtags.next().unwrap_or_default();
+        // the script subtag has four letters, e.g. "ku-Latn" or "pa-Arab"
+        match subtags.find(|item| item.len() == 4 && item.chars().all(|c| c.is_ascii_alphabetic()))
+        {
+            Some(script) if RTL_SCRIPTS.contains(&script.as_str()) => Self::Rtl,
+            Some(_) => Self::Ltr,
+            None if RTL_LANGUAGES.contains(&language.as_str()) => Self::Rtl,
+            None => Self::Ltr,
+        }
+    }
+
+    // value of the dir attribute, "auto" has no fixed direction
+    pub fn from_attribute(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "ltr" => Some(Self::Ltr),
+            "rtl" => Some(Self::Rtl),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ltr => "ltr",
+            Self::Rtl => "rtl",
+        }
+    }
+
+    // physical side where lines start, for mirrored layouts, e.g. `format!("margin-{}: 1em", direction.start())`
+    pub fn start(&self) -> &'static str {
+        match self {
+            Self::Ltr => "left",
+            Self::Rtl => "right",
+        }
+    }
+
+    pub fn end(&self) -> &'static str {
+        match self {
+            Self::Ltr => "right",
+            Self::Rtl => "left",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "\
+# comment
+-brand = Forse
+hello = Hello { $name }, welcome to { -brand }
+quoted = { \"{\" } braces { \"}\" }
+multiline = First line
+    second line
+login = Log in
+    .title = Log in to { -brand }
+emails = { $count ->
+    [0] No emails
+    [one] One email
+   *[other] { $count } emails
+}
+loop = { loop }
+";
+
+    fn catalog(locale: &str) -> Catalog {
+        Catalog::parse(locale, SOURCE).unwrap()
+    }
+
+    fn args(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn format(catalog: &Catalog, key: &str, items: &[(&str, &str)]) -> Option<String> {
+        catalog.format(key, &args(items))
+    }
+
+    #[test]
+    fn variables_and_terms_are_formatted() {
+        let catalog = catalog("en");
+        assert_eq!(
+            format(&catalog, "hello", &[("name", "Ada")]).unwrap(),
+            "Hello Ada, welcome to Forse"
+        );
+        // missing variables stay visible
+        assert_eq!(
+            format(&catalog, "hello", &[]).unwrap(),
+            "Hello {$name}, welcome to Forse"
+        );
+        assert_eq!(format(&catalog, "quoted", &[]).unwrap(), "{ braces }");
+        assert_eq!(format(&catalog, "missing", &[]), None);
+    }
+
+    #[test]
+    fn continuation_lines_and_attributes() {
+        let catalog = catalog("en");
+        assert_eq!(
+            format(&catalog, "multiline", &[]).unwrap(),
+            "First line\nsecond line"
+        );
+        assert_eq!(format(&catalog, "login", &[]).unwrap(), "Log in");
+        assert_eq!(
+            format(&catalog, "login.title", &[]).unwrap(),
+            "Log in to Forse"
+        );
+    }
+
+    #[test]
+    fn select_expressions_use_exact_matches_then_plural_categories() {
+        let english = catalog("en");
+        assert_eq!(
+            format(&english, "emails", &[("count", "0")]).unwrap(),
+            "No emails"
+        );
+        assert_eq!(
+            format(&english, "emails", &[("count", "1")]).unwrap(),
+            "One email"
+        );
+        assert_eq!(
+            format(&english, "emails", &[("count", "5")]).unwrap(),
+            "5 emails"
+        );
+        // without the argument the default variant is used
+        assert_eq!(format(&english, "emails", &[]).unwrap(), "{$count} emails");
+        // japanese has no "one" category
+        let japanese = catalog("ja");
+        assert_eq!(
+            format(&japanese, "emails", &[("count", "1")]).unwrap(),
+            "1 emails"
+        );
+    }
+
+    #[test]
+    fn recursive_references_stop() {
+        assert_eq!(format(&catalog("en"), "loop", &[]).unwrap(), "{loop}");
+    }
+
+    #[test]
+    fn malformed_messages_are_errors() {
+        let error = |source: &str| Catalog::parse("en", source).unwrap_err();
+        assert_eq!(error("hello world"), "Line 1: expected `key = value`");
+        assert_eq!(error("1key = x"), "Line 1: expected `key = value`");
+        assert_eq!(
+            error("    indented = x"),
+            "Line 1: indented line outside of a message"
+        );
+        assert_eq!(
+            error("ok = x\nhello = { $name"),
+            "Line 2: expected '}' in \"hello\""
+        );
+        assert_eq!(
+            error("hello = a } b"),
+            "Line 1: unexpected '}' in \"hello\""
+        );
+        assert_eq!(
+            error("hello = { \"open }"),
+            "Line 1: unterminated string literal in \"hello\""
+        );
+        assert_eq!(
+            error("hello = { }"),
+            "Line 1: expected an identifier in \"hello\""
+        );
+        assert_eq!(
+            error("emails = { $count ->\n    [one] One\n    [other] Many\n}"),
+            "Line 1: select expression without a *[default] variant in \"emails\""
+        );
+        assert_eq!(
+            error("emails = { $count ->\n    [one] One"),
+            "Line 1: unterminated select expression in \"emails\""
+        );
+    }
+
+    #[test]
+    fn plural_categories() {
+        assert_eq!(plural_category("en-US", 1.0), "one");
+        assert_eq!(plural_category("en", 1.5), "other");
+        assert_eq!(plural_category("fr", 0.0), "one");
+        assert_eq!(plural_category("ru", 22.0), "few");
+        assert_eq!(plural_category("ru", 12.0), "many");
+        assert_eq!(plural_category("ru", 1.5), "other");
+        // unlike russian the rest is "other" and the fraction digits count
+        assert_eq!(plural_category("hr", 21.0), "one");
+        assert_eq!(plural_category("sr", 3.0), "few");
+        assert_eq!(plural_category("bs", 13.0), "other");
+        assert_eq!(plural_category("hr", 5.0), "other");
+        assert_eq!(plural_category("hr", 0.1), "one");
+        assert_eq!(plural_category("hr", 1.11), "other");
+        assert_eq!(plural_category("sr", 2.3), "few");
+        assert_eq!(plural_category("sr", 0.5), "other");
+        assert_eq!(plural_category("pl", 5.0), "many");
+        assert_eq!(plural_category("ar", 0.0), "zero");
+        assert_eq!(plural_category("ar", 105.0), "few");
+    }
+
+    #[test]
+    fn translations_fall_back_by_language_then_default() {
+        let german = Catalog::parse("de", "hello = Hallo { $name }").unwrap();
+        let translations = Translations::new("en")
+            .catalog(catalog("en"))
+            .catalog(german);
+        let preferred = vec!["de-AT".to_string()];
+        let name = args(&[("name", "Ada")]);
+        assert_eq!(
+            translations.translate(&preferred, "hello", &name),
+            "Hallo Ada"
+        );
+        assert_eq!(translations.translate(&preferred, "login", &name), "Log in");
+        assert_eq!(
+            translations.translate(&preferred, "unknown", &name),
+            "unknown"
+        );
+    }
+}
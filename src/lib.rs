@@ -1,6 +1,23 @@
+pub mod a11y;
+pub mod aria;
 pub mod attribute;
+pub mod class_list;
+pub mod client;
+pub mod csrf;
+pub mod document;
 pub mod event;
+pub mod form;
+pub mod guard;
+pub mod html;
+pub mod i18n;
 pub mod node;
+pub mod render;
+pub mod response;
 pub mod router_view;
+#[cfg(feature = "http")]
+pub mod server;
 pub mod site;
+pub mod static_site;
+pub mod style;
 pub mod utils;
+pub mod validate;
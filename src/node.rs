@@ -1,15 +1,31 @@
+use std::rc::Rc;
+
 use crate::{
-    attribute::{Attribute, AttributeValue},
+    attribute::{Attribute, AttributeValue, Attributes},
+    csrf,
     event::HtmlAction,
+    i18n::{Direction, TranslationNode},
+    render::{
+        can_omit_end_tag, html_attribute, EventHandler, HandlerMode, Namespace, OutputFormat,
+        RenderOptions, RenderState, Sibling, Syntax,
+    },
     router_view::RouterView,
     site::Context,
-    utils::{parse_elem_properties, HTML_VOID_ELEMENTS},
+    style::{styles_css, StyleSheet},
+    utils::{
+        adjust_case, escape_html, parse_elem_properties, parse_query_string, to_js_string,
+        try_parse_elem_properties, HTML_BLOCK_ELEMENTS, HTML_PHRASING_CONTENT_ELEMENTS,
+        HTML_PRESERVE_WHITESPACE_ELEMENTS, HTML_VOID_ELEMENTS, MATHML_NAMESPACE,
+        SVG_CASED_ATTRIBUTES, SVG_CASED_ELEMENTS, SVG_NAMESPACE, XHTML_NAMESPACE, XLINK_NAMESPACE,
+    },
 };
 
 #[derive(Clone)]
 pub struct NodeProperties {
     pub tag: String,
-    pub attributes: Vec<Attribute>,
+    pub attributes: Attributes,
+    // scoped styles used in the class attribute
+    pub styles: Vec<StyleSheet>,
 }
 
 #[derive(Clone)]
@@ -17,68 +33,49 @@ pub enum Node {
     Tag(TagNode),
     Text(RawTextNode),
     Html(RawTextNode),
+    Translation(TranslationNode),
     RouterView(RouterView),
     None,
 }
 
 impl Node {
     pub fn to_html(&self, context: &Context) -> String {
+        self.to_html_with_options(context, &RenderOptions::default())
+    }
+
+    pub fn to_html_with_options(&self, context: &Context, options: &RenderOptions) -> String {
+        let mut state = RenderState::with_options(options.clone(), HandlerMode::default());
+        state.direction = context.direction;
+        self.render_html(context, &mut state)
+    }
+
+    pub fn render_html(&self, context: &Context, state: &mut RenderState) -> String {
         match self {
             Self::Tag(elem) => {
                 if let Some(properties) = &elem.properties {
-                    let tag: &str = &properties.tag.clone();
-                    let mut outer_before = String::new();
-                    let mut outer_after = String::new();
-                    let mut attributes_strings = vec![];
-                    for attribute in &properties.attributes {
-                        // todo: escape quotes?
-                        if attribute.key.to_lowercase() == "onclick" {
-                            match &attribute.value {
-                                AttributeValue::EventAction(value) => match &value.html_action {
-                                    HtmlAction::Redirect { url } => {
-                                        outer_before = "<a href=\"".to_string() + url + "\">";
-                                        outer_after = "</a>".to_string();
-                                    }
-                                },
-                                AttributeValue::String(_) => {}
-                            }
-                        } else {
-                            let value = attribute.to_inline_html_item();
-                            if value != "" {
-                                attributes_strings.push(value);
-                            }
-                        }
-                    }
-                    let mut attributes = attributes_strings.join(" ");
-                    if attributes.len() > 0 {
-                        attributes = " ".to_owned() + &attributes;
-                    }
-                    if !HTML_VOID_ELEMENTS.contains(&tag) {
-                        let inner: String = elem
-                            .children
-                            .iter()
-                            .map(|item| item.to_html(context))
-                            .collect();
-                        format!("{outer_before}<{tag}{attributes}>{inner}</{tag}>{outer_after}")
-                    } else {
-                        format!("{outer_before}<{tag}{attributes} />{outer_after}")
-                    }
+                    render_element(properties, &elem.children, context, state)
                 } else {
-                    elem.children
-                        .iter()
-                        .map(|item| item.to_html(context))
-                        .collect()
+                    let children = flatten_children(&elem.children, context);
+                    let separator = if children.iter().all(is_block_node) {
+                        state.line_break()
+                    } else {
+                        String::new()
+                    };
+                    render_children(&children, None, context, state).join(&separator)
                 }
             }
             Self::RouterView(view) => {
                 if let Some(node) = view.to_node(context) {
-                    node.to_html(context)
+                    node.render_html(context, state)
                 } else {
-                    (Self::None).to_html(context)
+                    (Self::None).render_html(context, state)
                 }
             }
-            Self::Text(text) => text.content.to_owned(), // todo: escape text
+            Self::Text(text) if state.raw_text => text.content.to_owned(),
+            // text can come from user input, e.g. the value of a submitted textarea
+            Self::Text(text) => escape_html(&text.content),
             Self::Html(html) => html.content.to_owned(),
+            Self::Translation(translation) => escape_html(&translation.resolve(context)),
             Self::None => String::from(""),
         }
     }
@@ -89,15 +86,16 @@ impl Node {
             Self::Tag(elem) => {
                 if let Some(properties) = &elem.properties {
                     let tag: &str = &properties.tag.clone();
+                    let csrf_children = csrf::form_children(properties, &elem.children, context);
+                    let children = csrf_children.as_deref().unwrap_or(&elem.children);
                     let mut inner_string = String::new();
                     if !HTML_VOID_ELEMENTS.contains(&tag) {
-                        let inner: Vec<String> = elem
-                            .children
+                        let inner: Vec<String> = children
                             .iter()
                             .map(|item| item.to_hyperscript(function_name, context))
-                            .filter(|item| item != "")
+                            .filter(|item| !item.is_empty())
                             .collect();
-                        if inner.len() > 0 {
+                        if !inner.is_empty() {
                             if inner.len() == 1 {
                                 inner_string = ",".to_string() + &inner[0];
                             } else {
@@ -107,13 +105,14 @@ impl Node {
                     }
                     let mut attributes_strings = vec![];
                     for attribute in &properties.attributes {
-                        let value = attribute.to_json_object_item(function_name);
-                        if value != "" {
+                        let value = csrf::protect_attribute(attribute, context)
+                            .to_json_object_item(function_name);
+                        if !value.is_empty() {
                             attributes_strings.push(value);
                         }
                     }
                     let mut attributes_final_string = String::new();
-                    if attributes_strings.len() > 0 {
+                    if !attributes_strings.is_empty() {
                         attributes_final_string =
                             ",{".to_string() + &attributes_strings.join(",") + "}";
                     }
@@ -123,7 +122,7 @@ impl Node {
                         .children
                         .iter()
                         .map(|item| item.to_hyperscript(function_name, context))
-                        .filter(|item| item != "")
+                        .filter(|item| !item.is_empty())
                         .collect();
                     result.join(",")
                 }
@@ -135,19 +134,510 @@ impl Node {
                     (Self::None).to_hyperscript(function_name, context)
                 }
             }
-            Self::Text(text) => {
-                // todo: escape text & doublequotes
-                let inner = text.content.to_owned();
-                format!("\"{inner}\"")
+            Self::Text(text) => to_js_string(&text.content),
+            Self::Html(html) => format!("{function_name}.trust({})", to_js_string(&html.content)),
+            Self::Translation(translation) => to_js_string(&translation.resolve(context)),
+            Self::None => String::from(""),
+        }
+    }
+
+    // data only representation for the client runtime, see `client::RUNTIME`
+    pub fn to_json(&self, context: &Context) -> String {
+        match self {
+            Self::Tag(elem) => {
+                let csrf_children = elem.properties.as_ref().and_then(|properties| {
+                    csrf::form_children(properties, &elem.children, context)
+                });
+                let children: Vec<String> = csrf_children
+                    .as_deref()
+                    .unwrap_or(&elem.children)
+                    .iter()
+                    .map(|item| item.to_json(context))
+                    .filter(|item| item != "null")
+                    .collect();
+                let children = "[".to_string() + &children.join(",") + "]";
+                if let Some(properties) = &elem.properties {
+                    let tag = to_js_string(&properties.tag);
+                    let attributes: Vec<String> = properties
+                        .attributes
+                        .iter()
+                        .map(|attribute| csrf::protect_attribute(attribute, context).to_json_item())
+                        .collect();
+                    let attributes = attributes.join(",");
+                    if HTML_VOID_ELEMENTS.contains(&properties.tag.as_str()) {
+                        format!("{{\"tag\":{tag},\"attrs\":{{{attributes}}}}}")
+                    } else {
+                        format!(
+                            "{{\"tag\":{tag},\"attrs\":{{{attributes}}},\"children\":{children}}}"
+                        )
+                    }
+                } else {
+                    children
+                }
             }
-            Self::Html(html) => {
-                // todo: change to coorect format
-                let inner = html.content.to_owned();
-                format!("\"{inner}\"")
+            Self::RouterView(view) => {
+                if let Some(node) = view.to_node(context) {
+                    node.to_json(context)
+                } else {
+                    (Self::None).to_json(context)
+                }
             }
-            Self::None => String::from(""),
+            Self::Text(text) => to_js_string(&text.content),
+            Self::Html(html) => format!("{{\"html\":{}}}", to_js_string(&html.content)),
+            Self::Translation(translation) => to_js_string(&translation.resolve(context)),
+            Self::None => String::from("null"),
+        }
+    }
+
+    // scoped styles of the tree, in document order
+    pub fn styles(&self, context: &Context) -> Vec<StyleSheet> {
+        match self {
+            Self::Tag(elem) => {
+                let mut styles = vec![];
+                if let Some(properties) = &elem.properties {
+                    styles.extend(properties.styles.iter().cloned());
+                }
+                for child in flatten_children(&elem.children, context) {
+                    styles.extend(child.styles(context));
+                }
+                styles
+            }
+            Self::RouterView(view) => view
+                .to_node(context)
+                .map(|node| node.styles(context))
+                .unwrap_or_default(),
+            Self::Text(_) | Self::Html(_) | Self::Translation(_) | Self::None => vec![],
+        }
+    }
+
+    // for client rendered output, the node preceded by a <style> with its scoped styles
+    pub fn with_style_element(&self, context: &Context) -> Node {
+        let css = styles_css(&self.styles(context));
+        if css.is_empty() {
+            return self.clone();
+        }
+        let mut attributes = vec![];
+        if let Some(nonce) = &context.csp_nonce {
+            attributes.push(Attribute::from("nonce", nonce.as_str()));
+        }
+        vec![elem("style", Some(attributes), css.as_str()), self.clone()].to_node()
+    }
+}
+
+fn render_element(
+    properties: &NodeProperties,
+    children: &[Node],
+    context: &Context,
+    state: &mut RenderState,
+) -> String {
+    let csrf_children = csrf::form_children(properties, children, context);
+    let children = csrf_children.as_deref().unwrap_or(children);
+    let parent_namespace = state.namespace;
+    let parent_element_namespace = state.parent_element_namespace;
+    let namespace = parent_namespace.of_element(&properties.tag);
+    // svg is case sensitive, e.g. `foreignObject` and `viewBox`
+    let tag: &str = &match namespace {
+        Namespace::Svg => adjust_case(&properties.tag, SVG_CASED_ELEMENTS),
+        Namespace::Html | Namespace::MathMl => properties.tag.clone(),
+    };
+    let (parent_tag, next_sibling) = state.siblings.take().unwrap_or((None, Sibling::Unknown));
+    let is_void = namespace == Namespace::Html && HTML_VOID_ELEMENTS.contains(&tag);
+    let has_attribute = |key: &str| properties.attributes.iter().any(|item| item.key == key);
+
+    let redirect = properties
+        .attributes
+        .iter()
+        .find_map(|attribute| match &attribute.value {
+            AttributeValue::EventAction(value) if attribute.key.eq_ignore_ascii_case("onclick") => {
+                match &value.html_action {
+                    HtmlAction::Redirect { url } => Some(url.to_owned()),
+                }
+            }
+            _ => None,
+        })
+        .map(|url| {
+            if state.in_anchor || (tag == "a" && has_attribute("href")) {
+                // links can't be nested, an explicit href wins
+                RedirectFallback::None
+            } else if tag == "a" {
+                RedirectFallback::Href(url)
+            } else if tag == "button" && state.in_form {
+                RedirectFallback::FormAction(url)
+            } else if tag == "button" && !state.in_phrasing {
+                RedirectFallback::Form(url)
+            } else if tag == "button" {
+                // a form around the button would close the <p>, and a link can't be inside a button
+                RedirectFallback::ButtonAnchor(url)
+            } else if state.in_phrasing && !is_void && HTML_BLOCK_ELEMENTS.contains(&tag) {
+                // a block element can't be wrapped in a link inside phrasing content
+                RedirectFallback::InnerAnchor(url)
+            } else {
+                RedirectFallback::OuterAnchor(url)
+            }
+        })
+        .unwrap_or(RedirectFallback::None);
+    let tag = match redirect {
+        RedirectFallback::ButtonAnchor(_) => "a",
+        _ => tag,
+    };
+
+    let mut outer_before = String::new();
+    let mut outer_after = String::new();
+    let mut wrapper_attributes = vec![];
+    let mut attributes_strings = vec![];
+    let mut element_id = None;
+    let mut handlers = vec![];
+    for attribute in &properties.attributes {
+        let key = match namespace {
+            Namespace::Svg => adjust_case(&attribute.key, SVG_CASED_ATTRIBUTES),
+            Namespace::Html | Namespace::MathMl => attribute.key.to_owned(),
+        };
+        let value = Attribute::new(&key, attribute.value.clone()).to_html_item(&state.options);
+        if value.is_empty() {
+            // event actions only have their html fallback
+        } else if redirect.moves_attribute(&key) {
+            wrapper_attributes.push(value);
+        } else if !redirect.replaces_attribute(&key) {
+            attributes_strings.push(value);
+        }
+        match &attribute.value {
+            AttributeValue::EventAction(value) => {
+                if let Some(event) = attribute.key.to_lowercase().strip_prefix("on") {
+                    let action = csrf::protect_action(&value.hyperscript_action, context);
+                    handlers.push((event.to_string(), action));
+                }
+            }
+            AttributeValue::String(value) => {
+                if attribute.key == "id" {
+                    element_id = Some(value.to_owned());
+                }
+            }
+            AttributeValue::Bool(_) | AttributeValue::ClassList(_) => {}
+        }
+    }
+
+    let self_closing_end = match (state.options.format, state.options.syntax) {
+        (OutputFormat::Minified, Syntax::Html) => "",
+        (OutputFormat::Minified, Syntax::Xhtml) => "/",
+        (OutputFormat::Compact | OutputFormat::Pretty, _) => " /",
+    };
+    let mut inner_before = String::new();
+    let mut inner_after = String::new();
+    match &redirect {
+        RedirectFallback::None => {}
+        RedirectFallback::Href(url) => {
+            attributes_strings.push(html_attribute("href", url, &state.options));
+        }
+        RedirectFallback::FormAction(url) => {
+            attributes_strings.push(html_attribute("type", "submit", &state.options));
+            attributes_strings.push(html_attribute("formaction", url, &state.options));
+            attributes_strings.push(html_attribute("formmethod", "get", &state.options));
+            // target becomes formtarget
+            attributes_strings.extend(wrapper_attributes.iter().map(|item| format!("form{item}")));
+        }
+        RedirectFallback::Form(url) => {
+            // get forms replace the query string of the action, so it's sent as hidden inputs
+            let (path, query) = url.split_once('?').unwrap_or((url, ""));
+            let hidden_inputs: String = parse_query_string(query)
+                .iter()
+                .map(|(key, value)| {
+                    let attributes = [
+                        html_attribute("type", "hidden", &state.options),
+                        html_attribute("name", key, &state.options),
+                        html_attribute("value", value, &state.options),
+                    ];
+                    format!("<input {}{self_closing_end}>", attributes.join(" "))
+                })
+                .collect();
+            wrapper_attributes.insert(0, html_attribute("action", path, &state.options));
+            wrapper_attributes.insert(0, html_attribute("method", "get", &state.options));
+            outer_before = format!("<form {}>{hidden_inputs}", wrapper_attributes.join(" "));
+            outer_after = "</form>".to_string();
+            attributes_strings.push(html_attribute("type", "submit", &state.options));
+        }
+        RedirectFallback::ButtonAnchor(url) => {
+            attributes_strings.insert(0, html_attribute("href", url, &state.options));
+            if !has_attribute("role") {
+                attributes_strings.push(html_attribute("role", "button", &state.options));
+            }
+        }
+        RedirectFallback::OuterAnchor(url) | RedirectFallback::InnerAnchor(url) => {
+            wrapper_attributes.insert(0, html_attribute("href", url, &state.options));
+            let before = format!("<a {}>", wrapper_attributes.join(" "));
+            if let RedirectFallback::InnerAnchor(_) = redirect {
+                inner_before = before;
+                inner_after = "</a>".to_string();
+            } else {
+                outer_before = before;
+                outer_after = "</a>".to_string();
+            }
+        }
+    }
+    // a lang switching the direction gets a dir unless the element has one
+    let string_attribute = |key: &str| {
+        properties
+            .attributes
+            .iter()
+            .find_map(|item| match &item.value {
+                AttributeValue::String(value) if item.key == key => Some(value.as_str()),
+                _ => None,
+            })
+    };
+    state.styles.extend(properties.styles.iter().cloned());
+    let parent_direction = state.direction;
+    if namespace == Namespace::Html {
+        let lang_direction = string_attribute("lang").map(Direction::of_locale);
+        match string_attribute("dir") {
+            Some(dir) => {
+                state.direction = Direction::from_attribute(dir).unwrap_or(parent_direction);
+            }
+            None => {
+                if let Some(direction) = lang_direction.filter(|item| *item != parent_direction) {
+                    attributes_strings.push(html_attribute(
+                        "dir",
+                        direction.as_str(),
+                        &state.options,
+                    ));
+                    state.direction = direction;
+                }
+            }
+        }
+    }
+
+    if state.handler_mode == HandlerMode::Script && !handlers.is_empty() {
+        let element_id = element_id.unwrap_or_else(|| {
+            let id = state.generate_id("forse-handler");
+            attributes_strings.push(html_attribute("id", &id, &state.options));
+            id
+        });
+        for (event, action) in handlers {
+            let prevent_default = match event.as_str() {
+                "click" => !matches!(redirect, RedirectFallback::None) || tag == "a",
+                "submit" => tag == "form",
+                _ => false,
+            };
+            state.handlers.push(EventHandler {
+                element_id: element_id.clone(),
+                event,
+                action,
+                prevent_default,
+            });
+        }
+    }
+    if tag == "script" {
+        if let Some(nonce) = &context.csp_nonce {
+            if !properties.attributes.iter().any(|item| item.key == "nonce") {
+                attributes_strings.push(html_attribute("nonce", nonce, &state.options));
+            }
+        }
+    }
+    // compared to the parent element, the html children of <foreignObject> need the xhtml namespace
+    if namespace != parent_element_namespace {
+        let has_attribute = |key: &str| properties.attributes.iter().any(|item| item.key == key);
+        let namespace_uri = match namespace {
+            Namespace::Svg => SVG_NAMESPACE,
+            Namespace::MathMl => MATHML_NAMESPACE,
+            Namespace::Html => XHTML_NAMESPACE,
+        };
+        if state.options.syntax == Syntax::Xhtml && !has_attribute("xmlns") {
+            attributes_strings.push(html_attribute("xmlns", namespace_uri, &state.options));
+        }
+        if !has_attribute("xmlns:xlink") && uses_xlink(&properties.attributes, children) {
+            attributes_strings.push(html_attribute(
+                "xmlns:xlink",
+                XLINK_NAMESPACE,
+                &state.options,
+            ));
+        }
+    }
+    let mut attributes = attributes_strings.join(" ");
+    if !attributes.is_empty() {
+        attributes = " ".to_owned() + &attributes;
+    }
+
+    if is_void {
+        return format!("{outer_before}<{tag}{attributes}{self_closing_end}>{outer_after}");
+    }
+
+    let children = flatten_children(children, context);
+    // svg and mathml elements can be self-closing, unlike the non-void html ones
+    if namespace != Namespace::Html && children.is_empty() {
+        // the space keeps an unquoted attribute value from taking the slash
+        let self_closing_end = if self_closing_end.is_empty() {
+            " /"
+        } else {
+            self_closing_end
+        };
+        return format!("{outer_before}<{tag}{attributes}{self_closing_end}>{outer_after}");
+    }
+    state.namespace = namespace.of_children(tag);
+    state.parent_element_namespace = namespace;
+    let (in_anchor, in_form, in_phrasing) = (state.in_anchor, state.in_form, state.in_phrasing);
+    state.in_anchor = in_anchor || tag == "a" || redirect.wraps_in_anchor();
+    state.in_form = in_form || tag == "form" || matches!(redirect, RedirectFallback::Form(_));
+    if HTML_PHRASING_CONTENT_ELEMENTS.contains(&tag) {
+        state.in_phrasing = true;
+    } else if HTML_BLOCK_ELEMENTS.contains(&tag) {
+        state.in_phrasing = false;
+    }
+    let raw_text = state.raw_text;
+    state.raw_text = namespace == Namespace::Html && (tag == "script" || tag == "style");
+    let preserve_whitespace = state.preserve_whitespace;
+    state.preserve_whitespace =
+        preserve_whitespace || HTML_PRESERVE_WHITESPACE_ELEMENTS.contains(&tag);
+    // children only get their own lines if that doesn't add whitespace between inline content
+    let is_block_layout = state.options.format == OutputFormat::Pretty
+        && !state.preserve_whitespace
+        && !children.is_empty()
+        && children.iter().all(is_block_node);
+    let inner = if is_block_layout {
+        state.depth += 1;
+        let line_break = state.line_break();
+        let rendered_children = render_children(&children, Some(tag), context, state);
+        state.depth -= 1;
+        line_break.clone() + &rendered_children.join(&line_break) + &state.line_break()
+    } else {
+        render_children(&children, Some(tag), context, state).concat()
+    };
+    state.preserve_whitespace = preserve_whitespace;
+    state.raw_text = raw_text;
+    state.namespace = parent_namespace;
+    state.parent_element_namespace = parent_element_namespace;
+    state.in_anchor = in_anchor;
+    state.in_form = in_form;
+    state.in_phrasing = in_phrasing;
+    state.direction = parent_direction;
+
+    let end_tag = if state.options.omit_optional_end_tags
+        && state.options.syntax == Syntax::Html
+        && namespace == Namespace::Html
+        && can_omit_end_tag(tag, parent_tag.as_deref(), &next_sibling)
+    {
+        String::new()
+    } else {
+        format!("</{tag}>")
+    };
+    format!(
+        "{outer_before}<{tag}{attributes}>{inner_before}{inner}{inner_after}{end_tag}{outer_after}"
+    )
+}
+
+// html output of an element with an `HtmlAction::Redirect` onclick action
+enum RedirectFallback {
+    None,
+    // for <a> elements
+    Href(String),
+    // for buttons inside a form
+    FormAction(String),
+    // for buttons outside of forms and phrasing content
+    Form(String),
+    // <a role="button"> instead of buttons in phrasing content outside of forms
+    ButtonAnchor(String),
+    // <a> inside the element, for block elements in phrasing content
+    InnerAnchor(String),
+    OuterAnchor(String),
+}
+
+impl RedirectFallback {
+    // link attributes on other elements are moved to the generated wrapper
+    fn moves_attribute(&self, key: &str) -> bool {
+        match self {
+            Self::OuterAnchor(_) | Self::InnerAnchor(_) => {
+                ["target", "rel", "download", "hreflang", "referrerpolicy"].contains(&key)
+            }
+            Self::Form(_) | Self::FormAction(_) => key == "target",
+            Self::None | Self::Href(_) | Self::ButtonAnchor(_) => false,
+        }
+    }
+
+    fn replaces_attribute(&self, key: &str) -> bool {
+        match self {
+            Self::Form(_) => key == "type",
+            Self::FormAction(_) => ["type", "formaction", "formmethod"].contains(&key),
+            // button only attributes
+            Self::ButtonAnchor(_) => {
+                ["type", "name", "value"].contains(&key) || key.starts_with("form")
+            }
+            Self::None | Self::Href(_) | Self::OuterAnchor(_) | Self::InnerAnchor(_) => false,
         }
     }
+
+    fn wraps_in_anchor(&self) -> bool {
+        matches!(
+            self,
+            Self::Href(_) | Self::ButtonAnchor(_) | Self::OuterAnchor(_) | Self::InnerAnchor(_)
+        )
+    }
+}
+
+// the end tag omission rules need to know the siblings of each child
+fn render_children(
+    children: &[Node],
+    parent_tag: Option<&str>,
+    context: &Context,
+    state: &mut RenderState,
+) -> Vec<String> {
+    let mut result = vec![];
+    for (index, child) in children.iter().enumerate() {
+        let next_sibling = match children.get(index + 1) {
+            Some(Node::Tag(TagNode {
+                properties: Some(properties),
+                ..
+            })) => Sibling::Element(properties.tag.to_owned()),
+            Some(_) => Sibling::Text,
+            None if parent_tag.is_some() => Sibling::End,
+            None => Sibling::Unknown,
+        };
+        state.siblings = Some((parent_tag.map(|tag| tag.to_string()), next_sibling));
+        result.push(child.render_html(context, state));
+        state.siblings = None;
+    }
+    result
+}
+
+// resolves fragments and router views so that the children can be laid out as a list
+pub(crate) fn flatten_children(children: &[Node], context: &Context) -> Vec<Node> {
+    let mut result = vec![];
+    for child in children {
+        match child {
+            Node::Tag(TagNode {
+                properties: None,
+                children,
+            }) => result.extend(flatten_children(children, context)),
+            Node::RouterView(view) => {
+                if let Some(node) = view.to_node(context) {
+                    result.extend(flatten_children(&[node], context));
+                }
+            }
+            Node::None => {}
+            _ => result.push(child.clone()),
+        }
+    }
+    result
+}
+
+fn uses_xlink(attributes: &[Attribute], children: &[Node]) -> bool {
+    attributes.iter().any(|item| item.key.starts_with("xlink:"))
+        || children.iter().any(|child| match child {
+            Node::Tag(elem) => uses_xlink(
+                elem.properties
+                    .as_ref()
+                    .map(|properties| properties.attributes.as_slice())
+                    .unwrap_or_default(),
+                &elem.children,
+            ),
+            _ => false,
+        })
+}
+
+fn is_block_node(node: &Node) -> bool {
+    match node {
+        Node::Tag(TagNode {
+            properties: Some(properties),
+            ..
+        }) => HTML_BLOCK_ELEMENTS.contains(&properties.tag.as_str()),
+        _ => false,
+    }
 }
 
 #[derive(Clone)]
@@ -173,6 +663,9 @@ pub trait ToNode {
     fn to_node(&self) -> Node;
 }
 
+#[cfg(feature = "derive")]
+pub use forse_web_derive::ToNode;
+
 impl ToNode for Node {
     fn to_node(&self) -> Node {
         self.clone()
@@ -183,31 +676,167 @@ impl<T: ToNode> ToNode for Vec<T> {
     fn to_node(&self) -> Node {
         Node::Tag(TagNode {
             properties: None,
-            children: self.into_iter().map(|item| item.to_node()).collect(),
+            children: self.iter().map(|item| item.to_node()).collect(),
         })
     }
 }
 
-impl ToNode for Option<Node> {
+impl<T: ToNode> ToNode for Option<T> {
     fn to_node(&self) -> Node {
-        if let Some(v) = self {
-            v.clone()
-        } else {
-            Node::None
+        match self {
+            Some(value) => value.to_node(),
+            None => Node::None,
         }
     }
 }
 
-impl ToNode for &str {
+impl<T: ToNode> ToNode for [T] {
+    fn to_node(&self) -> Node {
+        Node::Tag(TagNode {
+            properties: None,
+            children: self.iter().map(|item| item.to_node()).collect(),
+        })
+    }
+}
+
+impl<T: ToNode, const N: usize> ToNode for [T; N] {
+    fn to_node(&self) -> Node {
+        self.as_slice().to_node()
+    }
+}
+
+impl<T: ToNode + ?Sized> ToNode for &T {
+    fn to_node(&self) -> Node {
+        (**self).to_node()
+    }
+}
+
+impl<T: ToNode + ?Sized> ToNode for Box<T> {
+    fn to_node(&self) -> Node {
+        (**self).to_node()
+    }
+}
+
+impl<T: ToNode + ?Sized> ToNode for Rc<T> {
+    fn to_node(&self) -> Node {
+        (**self).to_node()
+    }
+}
+
+impl ToNode for str {
     fn to_node(&self) -> Node {
         Node::Text(RawTextNode::from(self))
     }
 }
 
+// text nodes of the display value
+macro_rules! display_to_node {
+    ($($type:ty),*) => {
+        $(
+            impl ToNode for $type {
+                fn to_node(&self) -> Node {
+                    Node::Text(RawTextNode::from(self))
+                }
+            }
+        )*
+    };
+}
+
+display_to_node!(
+    String, char, bool, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+// tuples are fragments of their items
+macro_rules! tuple_to_node {
+    ($(($($name:ident),+)),*) => {
+        $(
+            impl<$($name: ToNode),+> ToNode for ($($name,)+) {
+                #[allow(non_snake_case)]
+                fn to_node(&self) -> Node {
+                    let ($($name,)+) = self;
+                    Node::Tag(TagNode {
+                        properties: None,
+                        children: vec![$($name.to_node()),+],
+                    })
+                }
+            }
+        )*
+    };
+}
+
+tuple_to_node!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H)
+);
+
+// `items.iter().map(...).collect::<Node>()`
+impl<T: ToNode> FromIterator<T> for Node {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Node::Tag(TagNode {
+            properties: None,
+            children: iter.into_iter().map(|item| item.to_node()).collect(),
+        })
+    }
+}
+
+// a row per item in `table`, derived with `#[derive(ToNode)] #[node(table)]`
+pub trait TableRow {
+    fn header() -> Vec<Node>;
+    fn cells(&self) -> Vec<Node>;
+}
+
+pub fn table<T: TableRow>(rows: &[T]) -> Node {
+    let header: Vec<Node> = T::header()
+        .into_iter()
+        .map(|cell| elem("th[scope=col]", None, cell))
+        .collect();
+    let rows: Vec<Node> = rows
+        .iter()
+        .map(|row| {
+            let cells: Vec<Node> = row
+                .cells()
+                .into_iter()
+                .map(|cell| elem("td", None, cell))
+                .collect();
+            elem("tr", None, cells)
+        })
+        .collect();
+    elem(
+        "table",
+        None,
+        vec![
+            elem("thead", None, elem("tr", None, header)),
+            elem("tbody", None, rows),
+        ],
+    )
+}
+
+// like `elem` but selector and attribute conflicts are errors instead of the last value winning
+pub fn try_elem(
+    selector: &str,
+    attributes: Option<Vec<Attribute>>,
+    children: impl ToNode,
+) -> Result<Node, String> {
+    let mut properties = try_parse_elem_properties(selector, &attributes)?;
+    if properties.tag.is_empty() {
+        properties.tag = "div".to_string();
+    }
+    Ok(Node::Tag(TagNode {
+        properties: Some(properties),
+        children: vec![children.to_node()],
+    }))
+}
+
 // todo: macro for this
 pub fn elem(selector: &str, attributes: Option<Vec<Attribute>>, children: impl ToNode) -> Node {
     let mut properties = parse_elem_properties(selector, &attributes);
-    if properties.tag.len() == 0 {
+    if properties.tag.is_empty() {
         properties.tag = "div".to_string();
     }
     Node::Tag(TagNode {
@@ -215,3 +844,199 @@ pub fn elem(selector: &str, attributes: Option<Vec<Attribute>>, children: impl T
         children: vec![children.to_node()],
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventAction;
+
+    fn text_in(selector: &str, text: &str) -> Node {
+        elem(selector, None, text)
+    }
+
+    #[test]
+    fn text_is_escaped_in_html() {
+        let context = Context::new("/");
+        // was `<p><script>alert("x")</script> & more</p>`
+        assert_eq!(
+            text_in("p", "<script>alert(\"x\")</script> & more").to_html(&context),
+            "<p>&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; more</p>"
+        );
+    }
+
+    #[test]
+    fn script_and_style_text_is_raw() {
+        let context = Context::new("/");
+        assert_eq!(
+            text_in("script", "if (a < b && c) {}").to_html(&context),
+            "<script>if (a < b && c) {}</script>"
+        );
+        assert_eq!(
+            text_in("style", "a > b{color:red}").to_html(&context),
+            "<style>a > b{color:red}</style>"
+        );
+        // text in svg isn't raw text, only the html elements are
+        assert_eq!(
+            elem("svg", None, text_in("style", "a > b")).to_html(&context),
+            "<svg><style>a &gt; b</style></svg>"
+        );
+    }
+
+    #[test]
+    fn html_nodes_are_not_escaped() {
+        let context = Context::new("/");
+        let node = elem("p", None, Node::Html(RawTextNode::from("<b>bold</b>")));
+        assert_eq!(node.to_html(&context), "<p><b>bold</b></p>");
+    }
+
+    #[test]
+    fn html_nodes_are_trusted_strings_in_hyperscript() {
+        let context = Context::new("/");
+        let node = Node::Html(RawTextNode::from("<b title=\"a\">bold</b>\nnext"));
+        // was the raw html in quotes, which broke on quotes and newlines
+        assert_eq!(
+            node.to_hyperscript("m", &context),
+            "m.trust(\"\\u003cb title=\\\"a\\\"\\u003ebold\\u003c/b\\u003e\\nnext\")"
+        );
+    }
+
+    #[test]
+    fn attribute_values_are_escaped_in_html() {
+        let context = Context::new("/");
+        let node = elem(
+            "input",
+            Some(vec![Attribute::from("value", "say \"hi\" & <bye>")]),
+            Node::None,
+        );
+        // was `value="say "hi" & <bye>"`, which ends the attribute at the first quote
+        assert_eq!(
+            node.to_html(&context),
+            "<input value=\"say &quot;hi&quot; &amp; <bye>\" />"
+        );
+    }
+
+    #[test]
+    fn hyperscript_strings_are_escaped() {
+        let context = Context::new("/");
+        let node = elem(
+            "p",
+            Some(vec![Attribute::from("title", "a \"b\"")]),
+            "</script>\"",
+        );
+        // was `m("p",{"title":"a "b""},"</script>"")`
+        assert_eq!(
+            node.to_hyperscript("m", &context),
+            "m(\"p\",{\"title\":\"a \\\"b\\\"\"},\"\\u003c/script\\u003e\\\"\")"
+        );
+    }
+
+    #[test]
+    fn hyperscript_event_actions_are_functions() {
+        let context = Context::new("/");
+        let node = elem(
+            "button",
+            Some(vec![Attribute::from(
+                "onclick",
+                EventAction::ajax_default("/a", None),
+            )]),
+            "a",
+        );
+        // was a quoted string, which mithril sets as an attribute instead of a listener
+        assert_eq!(
+            node.to_hyperscript("m", &context),
+            "m(\"button\",{\"onclick\":function(){m.request({url:\"/a\",method:\"GET\"})}},\"a\")"
+        );
+    }
+
+    #[test]
+    fn xhtml_foreign_object_children_get_the_xhtml_namespace() {
+        let context = Context::new("/");
+        let node = elem(
+            "svg",
+            None,
+            elem(
+                "foreignObject",
+                None,
+                elem("div", None, elem("p", None, "x")),
+            ),
+        );
+        assert_eq!(
+            node.to_html_with_options(&context, &RenderOptions::xhtml()),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\"><foreignObject>\
+             <div xmlns=\"http://www.w3.org/1999/xhtml\"><p>x</p></div>\
+             </foreignObject></svg>"
+        );
+        // html syntax doesn't need the namespaces
+        assert_eq!(
+            node.to_html(&context),
+            "<svg><foreignObject><div><p>x</p></div></foreignObject></svg>"
+        );
+    }
+
+    fn redirect_button(url: &str) -> Node {
+        elem(
+            "button",
+            Some(vec![Attribute::from(
+                "onclick",
+                EventAction::ajax_default(url, None),
+            )]),
+            "go",
+        )
+    }
+
+    #[test]
+    fn redirect_buttons_get_a_form() {
+        let context = Context::new("/");
+        assert_eq!(
+            elem("div", None, redirect_button("/a?b=c")).to_html(&context),
+            "<div><form method=\"get\" action=\"/a\"><input type=\"hidden\" name=\"b\" value=\"c\" />\
+             <button type=\"submit\">go</button></form></div>"
+        );
+    }
+
+    #[test]
+    fn redirect_buttons_in_phrasing_content_become_links() {
+        let context = Context::new("/");
+        // a form would close the <p> and leave the button outside of it,
+        // and a link inside the button isn't valid either
+        assert_eq!(
+            elem("p", None, redirect_button("/a")).to_html(&context),
+            "<p><a href=\"/a\" role=\"button\">go</a></p>"
+        );
+        let button = elem(
+            "button[type=button][name=x][role=link]",
+            Some(vec![Attribute::from(
+                "onclick",
+                EventAction::ajax_default("/a", None),
+            )]),
+            "go",
+        );
+        assert_eq!(
+            elem("span", None, button).to_html(&context),
+            "<span><a href=\"/a\" role=\"link\">go</a></span>"
+        );
+        let node = elem(
+            "p",
+            None,
+            elem(
+                "button",
+                Some(vec![Attribute::from(
+                    "onclick",
+                    EventAction::ajax_default("/a", None),
+                )]),
+                elem("a[href=/b]", None, "x"),
+            ),
+        );
+        let kinds: Vec<_> = crate::validate::validate(&node, &context)
+            .into_iter()
+            .map(|item| item.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                crate::validate::DiagnosticKind::NestedAnchor,
+                crate::validate::DiagnosticKind::InteractiveInButton
+            ]
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use crate::{
+    event::HyperscriptAction,
+    i18n::Direction,
+    style::{styles_css, StyleSheet},
+    utils::{to_js_string, HTML_PARAGRAPH_CLOSING_ELEMENTS},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum HandlerMode {
+    // event actions only get their html fallback, e.g. the <a> wrapper of `HtmlAction::Redirect`
+    #[default]
+    Omit,
+    // event actions are also collected and attached by a single script, keyed by element id
+    Script,
+}
+
+pub struct EventHandler {
+    pub element_id: String,
+    pub event: String,
+    pub action: HyperscriptAction,
+    // the action replaces the html fallback, e.g. the redirect link of a click or the submit of a form
+    pub prevent_default: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum OutputFormat {
+    // single line, void elements as `<br />`
+    #[default]
+    Compact,
+    // one block element per line, see `RenderOptions::indent`
+    Pretty,
+    // single line, void elements as `<br>`
+    Minified,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Syntax {
+    #[default]
+    Html,
+    // well-formed xml: every element is closed, attributes are always quoted
+    Xhtml,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Namespace {
+    #[default]
+    Html,
+    Svg,
+    MathMl,
+}
+
+impl Namespace {
+    // namespace of the element with this tag, given the namespace of its parent's children
+    pub fn of_element(&self, tag: &str) -> Self {
+        match (self, tag) {
+            (Self::Html, "svg") => Self::Svg,
+            (Self::Html, "math") => Self::MathMl,
+            (namespace, _) => *namespace,
+        }
+    }
+
+    // namespace of the children of an element in this namespace
+    pub fn of_children(&self, tag: &str) -> Self {
+        match (self, tag) {
+            (Self::Svg, "foreignObject") => Self::Html,
+            (namespace, _) => *namespace,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    pub format: OutputFormat,
+    pub syntax: Syntax,
+    pub indent: String,
+    // e.g. `</li>` before another `<li>`, https://html.spec.whatwg.org/multipage/syntax.html#optional-tags
+    pub omit_optional_end_tags: bool,
+    // `id=x` instead of `id="x"` when the value allows it
+    pub minimize_attribute_quotes: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::default(),
+            syntax: Syntax::default(),
+            indent: "  ".to_string(),
+            omit_optional_end_tags: false,
+            minimize_attribute_quotes: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn pretty() -> Self {
+        Self {
+            format: OutputFormat::Pretty,
+            ..Default::default()
+        }
+    }
+
+    pub fn xhtml() -> Self {
+        Self {
+            syntax: Syntax::Xhtml,
+            ..Default::default()
+        }
+    }
+
+    pub fn minified() -> Self {
+        Self {
+            format: OutputFormat::Minified,
+            omit_optional_end_tags: true,
+            minimize_attribute_quotes: true,
+            ..Default::default()
+        }
+    }
+}
+
+// mutable state of a single html render, shared by all nodes of the render
+#[derive(Default)]
+pub struct RenderState {
+    pub options: RenderOptions,
+    pub handler_mode: HandlerMode,
+    pub handlers: Vec<EventHandler>,
+    // current element depth, for pretty output
+    pub depth: usize,
+    // inside <pre>, <textarea> and other elements where added whitespace would change the content
+    pub preserve_whitespace: bool,
+    // inside <script> and <style>, where text is written as is
+    pub raw_text: bool,
+    // namespace of the children of the element being rendered
+    pub namespace: Namespace,
+    // namespace of the element itself, differs from `namespace` for <foreignObject>
+    pub parent_element_namespace: Namespace,
+    // context of the element being rendered, for the html fallback of redirect actions
+    pub in_anchor: bool,
+    pub in_form: bool,
+    pub in_phrasing: bool,
+    // direction of the element being rendered, starts with `Context::direction`
+    pub direction: Direction,
+    // scoped styles of the rendered elements, can contain duplicates
+    pub styles: Vec<StyleSheet>,
+    // parent tag and next sibling of the element about to be rendered
+    pub siblings: Option<(Option<String>, Sibling)>,
+    next_id: usize,
+}
+
+impl RenderState {
+    pub fn new(handler_mode: HandlerMode) -> Self {
+        Self {
+            handler_mode,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_options(options: RenderOptions, handler_mode: HandlerMode) -> Self {
+        Self {
+            options,
+            handler_mode,
+            ..Default::default()
+        }
+    }
+
+    // newline and indentation for the current depth in pretty output, empty otherwise
+    pub fn line_break(&self) -> String {
+        if self.options.format == OutputFormat::Pretty && !self.preserve_whitespace {
+            "\n".to_string() + &self.options.indent.repeat(self.depth)
+        } else {
+            String::new()
+        }
+    }
+
+    // unique within the render
+    pub fn generate_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}-{}", self.next_id)
+    }
+
+    // css of the collected styles, empty if there are none
+    pub fn styles_css(&self) -> String {
+        styles_css(&self.styles)
+    }
+
+    // script body that attaches the collected handlers, empty if there are none
+    pub fn handlers_script(&self, function_name: &str) -> String {
+        if self.handlers.is_empty() {
+            return String::new();
+        }
+        let handlers: Vec<String> = self
+            .handlers
+            .iter()
+            .map(|handler| {
+                format!(
+                    "[{},{},{},{}]",
+                    to_js_string(&handler.element_id),
+                    to_js_string(&handler.event),
+                    handler.action.to_hyperscript(function_name),
+                    handler.prevent_default
+                )
+            })
+            .collect();
+        format!(
+            "[{}].forEach(function(h){{var e=document.getElementById(h[0]);if(e)e.addEventListener(h[1],function(event){{if(h[3])event.preventDefault();h[2].call(e,event)}})}});",
+            handlers.join(",")
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Sibling {
+    Element(String),
+    Text,
+    // last child of its parent
+    End,
+    // top level node, the parent isn't known
+    Unknown,
+}
+
+// https://html.spec.whatwg.org/multipage/syntax.html#optional-tags
+pub fn can_omit_end_tag(tag: &str, parent_tag: Option<&str>, next_sibling: &Sibling) -> bool {
+    let next_is = |tags: &[&str]| match next_sibling {
+        Sibling::Element(next_tag) => tags.contains(&next_tag.as_str()),
+        _ => false,
+    };
+    let is_end = *next_sibling == Sibling::End;
+    match tag {
+        "li" => is_end || next_is(&["li"]),
+        "dt" => next_is(&["dt", "dd"]),
+        "dd" => is_end || next_is(&["dt", "dd"]),
+        "p" => {
+            let closes_paragraph = next_is(HTML_PARAGRAPH_CLOSING_ELEMENTS);
+            let parent_allows_end = !matches!(
+                parent_tag,
+                Some("a" | "audio" | "del" | "ins" | "map" | "noscript" | "video")
+            );
+            closes_paragraph || (is_end && parent_allows_end)
+        }
+        "optgroup" => is_end || next_is(&["optgroup"]),
+        "option" => is_end || next_is(&["option", "optgroup"]),
+        "thead" => next_is(&["tbody", "tfoot"]),
+        "tbody" => is_end || next_is(&["tbody", "tfoot"]),
+        "tfoot" => is_end,
+        "tr" => is_end || next_is(&["tr"]),
+        "td" | "th" => is_end || next_is(&["td", "th"]),
+        _ => false,
+    }
+}
+
+// quotes are left out only when the value can't be misread without them
+pub fn html_attribute(key: &str, value: &str, options: &RenderOptions) -> String {
+    let can_skip_quotes = options.minimize_attribute_quotes
+        && options.syntax == Syntax::Html
+        && !value.is_empty()
+        && !value
+            .chars()
+            .any(|character| character.is_whitespace() || "\"'=<>`".contains(character));
+    if can_skip_quotes {
+        // unquoted values still end character references, e.g. `title=x&copy`
+        format!("{key}={}", value.replace('&', "&amp;"))
+    } else {
+        let value = value.replace('&', "&amp;").replace('"', "&quot;");
+        format!("{key}=\"{value}\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attribute::Attribute,
+        event::EventAction,
+        node::{elem, Node},
+        site::Context,
+    };
+
+    fn list() -> Node {
+        elem(
+            "div#list",
+            None,
+            (
+                elem("h1", None, ("Items ", elem("em", None, "all"))),
+                elem("ul", None, (elem("li", None, "a"), elem("li", None, "b"))),
+                elem("hr", None, Node::None),
+            ),
+        )
+    }
+
+    #[test]
+    fn pretty_output_puts_blocks_on_their_own_lines() {
+        let context = Context::new("/");
+        assert_eq!(
+            list().to_html_with_options(&context, &RenderOptions::pretty()),
+            "<div id=\"list\">\n  <h1>Items <em>all</em></h1>\n  <ul>\n    <li>a</li>\n    <li>b</li>\n  </ul>\n  <hr />\n</div>"
+        );
+    }
+
+    #[test]
+    fn minified_output_omits_quotes_and_end_tags() {
+        let context = Context::new("/");
+        assert_eq!(
+            list().to_html_with_options(&context, &RenderOptions::minified()),
+            "<div id=list><h1>Items <em>all</em></h1><ul><li>a<li>b</ul><hr></div>"
+        );
+    }
+
+    #[test]
+    fn end_tags_are_omitted_by_the_next_sibling() {
+        let omit =
+            |tag: &str, parent: Option<&str>, next: Sibling| can_omit_end_tag(tag, parent, &next);
+        assert!(omit("li", Some("ul"), Sibling::Element("li".to_string())));
+        assert!(!omit("li", Some("ul"), Sibling::Text));
+        assert!(omit("p", Some("div"), Sibling::Element("ul".to_string())));
+        assert!(!omit(
+            "p",
+            Some("div"),
+            Sibling::Element("span".to_string())
+        ));
+        // the end of an <a> parent would be taken as the end of the paragraph
+        assert!(!omit("p", Some("a"), Sibling::End));
+        assert!(omit("p", Some("div"), Sibling::End));
+        assert!(!omit("li", None, Sibling::Unknown));
+    }
+
+    #[test]
+    fn handlers_only_prevent_the_default_of_html_fallbacks() {
+        let context = Context::new("/");
+        let action = || EventAction::ajax_default("/a", None);
+        let node = elem(
+            "div",
+            None,
+            vec![
+                elem(
+                    "a#link",
+                    Some(vec![Attribute::from("onclick", action())]),
+                    "a",
+                ),
+                elem(
+                    "form#form",
+                    Some(vec![Attribute::from("onsubmit", action())]),
+                    Node::None,
+                ),
+                elem(
+                    "input#input",
+                    Some(vec![Attribute::from("oninput", action())]),
+                    Node::None,
+                ),
+            ],
+        );
+        let mut state = RenderState::new(HandlerMode::Script);
+        node.render_html(&context, &mut state);
+        let flags: Vec<_> = state
+            .handlers
+            .iter()
+            .map(|handler| (handler.element_id.as_str(), handler.prevent_default))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![("link", true), ("form", true), ("input", false)]
+        );
+        let script = state.handlers_script("m");
+        assert!(script.contains(
+            "[\"input\",\"input\",function(){m.request({url:\"/a\",method:\"GET\"})},false]"
+        ));
+        assert!(script.contains("if(h[3])event.preventDefault();"));
+    }
+
+    #[test]
+    fn unquoted_attribute_values_escape_ampersands() {
+        let options = RenderOptions::minified();
+        assert_eq!(
+            html_attribute("title", "x&copy", &options),
+            "title=x&amp;copy"
+        );
+        assert_eq!(html_attribute("title", "a b", &options), "title=\"a b\"");
+    }
+}
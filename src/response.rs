@@ -0,0 +1,154 @@
+use crate::{
+    document::Document,
+    node::{Node, TagNode},
+    site::{Context, ResponseFormat},
+};
+
+#[derive(Clone)]
+pub enum ResponseBody {
+    Node(Node),
+    Redirect(String),
+}
+
+#[derive(Clone)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: ResponseBody,
+}
+
+impl Response {
+    pub fn new(node: Node) -> Self {
+        Self {
+            status: 200,
+            headers: vec![],
+            body: ResponseBody::Node(node),
+        }
+    }
+
+    pub fn not_found(node: Node) -> Self {
+        Self::new(node).status(404)
+    }
+
+    // 302 by default, use `status` for other redirect codes
+    pub fn redirect(url: &str) -> Self {
+        Self {
+            status: 302,
+            headers: vec![("Location".to_string(), url.to_string())],
+            body: ResponseBody::Redirect(url.to_string()),
+        }
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    // replaces headers with the same name (case insensitive)
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers
+            .retain(|(header_key, _)| !header_key.eq_ignore_ascii_case(key));
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn get_header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_key, _)| header_key.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+
+    // renders the body in the format negotiated for the request, returns the content type and the body
+    pub fn render_body(
+        &self,
+        context: &Context,
+        document: &Document,
+        function_name: &str,
+    ) -> (&'static str, String) {
+        match &self.body {
+            ResponseBody::Node(node) => {
+                let body = match context.response_format {
+                    ResponseFormat::Html => document.to_html(node, context),
+                    ResponseFormat::Hyperscript => {
+                        let node = node.with_style_element(context);
+                        let hyperscript = node.to_hyperscript(function_name, context);
+                        // the client evaluates `return <body>`, so a fragment, e.g. the <style> and the content,
+                        // has to be one array expression, `a,b` would only return b
+                        match node {
+                            Node::Tag(TagNode {
+                                properties: None, ..
+                            }) => format!("[{hyperscript}]"),
+                            _ => hyperscript,
+                        }
+                    }
+                    ResponseFormat::Json => node.with_style_element(context).to_json(context),
+                };
+                (context.response_format.content_type(), body)
+            }
+            ResponseBody::Redirect(_) => ("text/plain; charset=utf-8", String::new()),
+        }
+    }
+
+    pub fn node(&self) -> Option<&Node> {
+        match &self.body {
+            ResponseBody::Node(node) => Some(node),
+            ResponseBody::Redirect(_) => None,
+        }
+    }
+}
+
+impl From<Node> for Response {
+    fn from(node: Node) -> Self {
+        Self::new(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attribute::Attribute, node::elem, node::ToNode, style::StyleSheet};
+
+    fn hyperscript(node: Node) -> String {
+        let context = Context::builder("/")
+            .header("X-Requested-With", "XMLHttpRequest")
+            .build();
+        Response::new(node)
+            .render_body(&context, &Document::new(), "m")
+            .1
+    }
+
+    #[test]
+    fn fragments_are_one_hyperscript_array() {
+        assert_eq!(hyperscript(elem("p", None, "a")), "m(\"p\",\"a\")");
+        assert_eq!(
+            hyperscript(vec![elem("p", None, "a"), elem("p", None, "b")].to_node()),
+            "[m(\"p\",\"a\"),m(\"p\",\"b\")]"
+        );
+    }
+
+    #[test]
+    fn styles_are_sent_before_the_content() {
+        let style = StyleSheet::new("card").declarations("color: red");
+        let node = elem("div", Some(vec![Attribute::from("class", &style)]), "a");
+        let class_name = style.class_name();
+        assert_eq!(
+            hyperscript(node),
+            format!(
+                "[m(\"style\",\".{class_name}{{color: red}}\"),\
+                 m(\"div\",{{\"className\":\"{class_name}\"}},\"a\")]"
+            )
+        );
+    }
+
+    #[test]
+    fn css_cant_close_the_style_element() {
+        let style = StyleSheet::new("card").rule("&::after", "content: \"</style><script>\"");
+        let node = elem("div", Some(vec![Attribute::from("class", &style)]), "a");
+        let html = Response::new(node)
+            .render_body(&Context::new("/"), &Document::new(), "m")
+            .1;
+        assert!(html.contains("::after{content: \"<\\/style><script>\"}</style>"));
+        assert_eq!(html.matches("</style>").count(), 1);
+    }
+}
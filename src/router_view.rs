@@ -1,12 +1,27 @@
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc};
 
-use crate::{node::Node, site::Context};
+use crate::{
+    guard::Guard,
+    node::Node,
+    response::Response,
+    site::Context,
+    utils::{build_query_string, percent_decode, percent_encode, to_js_string},
+};
+
+pub type RouteParams = HashMap<String, String>;
+pub type RouteCallback = Box<dyn Fn(&Context, &RouteParams) -> Response>;
 
 #[derive(Clone)]
 pub struct RouterView {
     content: RouterViewContent,
 }
 
+impl Default for RouterView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RouterView {
     pub fn new() -> Self {
         Self {
@@ -22,6 +37,14 @@ impl RouterView {
         let mut final_routes = vec![];
         let mut default_route = None;
         for path in routes {
+            if let Some(name) = &path.name {
+                if final_routes
+                    .iter()
+                    .any(|item: &Rc<RouterPath>| item.name.as_ref() == Some(name))
+                {
+                    return Err(format!("Duplicate route name \"{name}\""));
+                }
+            }
             let is_default_path = path.path == default_route_path;
             let new_path = Rc::new(path);
             if is_default_path {
@@ -30,37 +53,167 @@ impl RouterView {
             final_routes.push(new_path);
         }
         if let Some(value) = default_route {
-            self.content = RouterViewContent {
-                routes: final_routes,
-                default_route: Some(value),
-            };
+            self.content.routes = final_routes;
+            self.content.default_route = Some(value);
             Ok(())
         } else {
             Err("Invalid default route path".to_string())
         }
     }
 
+    // used instead of the default route when no route matches the request path
+    pub fn not_found<R: Into<Response>>(
+        &mut self,
+        content_callback: impl Fn(&Context, &RouteParams) -> R + 'static,
+    ) {
+        self.content.not_found_route = Some(Rc::new(RouterPath::new("", content_callback)));
+    }
+
+    // router guards run before the guards of the matched route
+    pub fn guard(&mut self, guard: Guard) {
+        self.content.guards.push(guard);
+    }
+
     // explicit, not impl ToNode
     pub fn to_node(&self, context: &Context) -> Option<Node> {
+        self.render(context).into_response().node().cloned()
+    }
+
+    pub fn render(&self, context: &Context) -> RouteResult {
         for route in &self.content.routes {
-            // todo
-            if true {
-                return Some((route.content)());
+            if let Some(params) = route.match_path(&context.request_path) {
+                for guard in self.content.guards.iter().chain(route.guards.iter()) {
+                    if let Err(response) = guard.run(context, &params) {
+                        return RouteResult::Blocked(response);
+                    }
+                }
+                return RouteResult::Found((route.content)(context, &params));
             }
         }
 
-        if let Some(route) = &self.content.default_route {
-            Some((route.content)())
-        } else {
-            None
+        // without a not found handler the default route is rendered with a 404 status,
+        // other statuses like redirects are kept
+        let route = self
+            .content
+            .not_found_route
+            .as_ref()
+            .or(self.content.default_route.as_ref());
+        match route {
+            Some(route) => {
+                let mut response = (route.content)(context, &RouteParams::new());
+                if response.status == 200 {
+                    response.status = 404;
+                }
+                RouteResult::NotFound(response)
+            }
+            None => RouteResult::NotFound(Response::not_found(Node::None)),
+        }
+    }
+
+    // route table for m.route, each route resolves its content by requesting the hyperscript of its path,
+    // it's evaluated with `new Function` so a content-security-policy needs 'unsafe-eval',
+    // `to_mithril_json_routes` works without it
+    pub fn to_mithril_routes(&self, function_name: &str) -> String {
+        self.mithril_routes(function_name, &format!("return {function_name}.request({{url:requestedPath,headers:{{\"X-Requested-With\":\"XMLHttpRequest\",\"Accept\":\"application/javascript\"}},responseType:\"text\",extract:function(xhr){{return xhr.responseText}}}}).then(function(source){{var content=(new Function(\"{function_name}\",\"return \"+source))({function_name});return {{view:function(){{return content}}}}}})"))
+    }
+
+    // same as `to_mithril_routes` but requests the json node tree, needs `client::RUNTIME`
+    pub fn to_mithril_json_routes(&self, function_name: &str) -> String {
+        self.mithril_routes(
+            function_name,
+            &format!("return forse.load({function_name},requestedPath)"),
+        )
+    }
+
+    pub(crate) fn mithril_routes(&self, function_name: &str, loader: &str) -> String {
+        let mut routes_strings = vec![];
+        for route in &self.content.routes {
+            let guards: String = self
+                .content
+                .guards
+                .iter()
+                .chain(route.guards.iter())
+                .map(|guard| guard.to_hyperscript(function_name))
+                .collect();
+            let path = to_js_string(&route.path);
+            routes_strings.push(format!(
+                "{path}:{{onmatch:function(args,requestedPath){{{guards}{loader}}}}}"
+            ));
+        }
+        "{".to_string() + &routes_strings.join(",") + "}"
+    }
+
+    // see `to_mithril_routes` for the content-security-policy
+    pub fn to_mithril_router(&self, function_name: &str, root_expression: &str) -> String {
+        let routes = self.to_mithril_routes(function_name);
+        self.mithril_router(function_name, root_expression, &routes)
+    }
+
+    pub fn to_mithril_json_router(&self, function_name: &str, root_expression: &str) -> String {
+        let routes = self.to_mithril_json_routes(function_name);
+        self.mithril_router(function_name, root_expression, &routes)
+    }
+
+    pub(crate) fn mithril_router(
+        &self,
+        function_name: &str,
+        root_expression: &str,
+        routes: &str,
+    ) -> String {
+        let default_path = match &self.content.default_route {
+            Some(route) => to_js_string(&route.path),
+            None => to_js_string("/"),
+        };
+        format!("{function_name}.route({root_expression},{default_path},{routes})")
+    }
+
+    // builds the path of a named route, parameters that don't appear in the path go to the query string
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String, String> {
+        let route = self
+            .content
+            .routes
+            .iter()
+            .find(|route| route.name.as_deref() == Some(name))
+            .ok_or_else(|| format!("Unknown route name \"{name}\""))?;
+        route.to_url(params)
+    }
+
+    pub fn routes(&self) -> &[Rc<RouterPath>] {
+        &self.content.routes
+    }
+}
+
+pub enum RouteResult {
+    Found(Response),
+    // a guard returned this response instead of the route content
+    Blocked(Response),
+    NotFound(Response),
+}
+
+impl RouteResult {
+    pub fn response(&self) -> &Response {
+        match self {
+            Self::Found(response) | Self::Blocked(response) | Self::NotFound(response) => response,
         }
     }
+
+    pub fn into_response(self) -> Response {
+        match self {
+            Self::Found(response) | Self::Blocked(response) | Self::NotFound(response) => response,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::NotFound(_))
+    }
 }
 
 #[derive(Clone)]
 struct RouterViewContent {
     pub routes: Vec<Rc<RouterPath>>,
     pub default_route: Option<Rc<RouterPath>>,
+    pub not_found_route: Option<Rc<RouterPath>>,
+    pub guards: Vec<Guard>,
 }
 
 impl RouterViewContent {
@@ -68,20 +221,274 @@ impl RouterViewContent {
         RouterViewContent {
             routes: Default::default(),
             default_route: None,
+            not_found_route: None,
+            guards: vec![],
         }
     }
 }
 
 pub struct RouterPath {
     pub path: String,
-    pub content: Box<dyn Fn() -> Node>,
+    pub name: Option<String>,
+    pub guards: Vec<Guard>,
+    pub content: RouteCallback,
 }
 
 impl RouterPath {
-    pub fn new(path: &str, content_callback: impl Fn() -> Node + 'static) -> Self {
+    // the callback can return a `Node` or a `Response` to control the status, headers or redirect
+    pub fn new<R: Into<Response>>(
+        path: &str,
+        content_callback: impl Fn(&Context, &RouteParams) -> R + 'static,
+    ) -> Self {
         Self {
             path: path.to_string(),
-            content: Box::new(content_callback),
+            name: None,
+            guards: vec![],
+            content: Box::new(move |context, params| content_callback(context, params).into()),
         }
     }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn guard(mut self, guard: Guard) -> Self {
+        self.guards.push(guard);
+        self
+    }
+
+    pub fn is_parametric(&self) -> bool {
+        self.path.split('/').any(|segment| segment.starts_with(':'))
+    }
+
+    // see `RouterView::url_for`
+    pub fn to_url(&self, params: &[(&str, &str)]) -> Result<String, String> {
+        let name = self.name.as_deref().unwrap_or(&self.path);
+        let mut used_params = vec![];
+        let mut segments = vec![];
+        for segment in self.path.split('/') {
+            if let Some(key) = segment.strip_prefix(':') {
+                // same as mithril, "/:path..." matches the rest of the url including slashes
+                let (key, is_variadic) = match key.strip_suffix("...") {
+                    Some(key) => (key, true),
+                    None => (key, false),
+                };
+                let value = params
+                    .iter()
+                    .find(|(param_key, _)| *param_key == key)
+                    .map(|(_, value)| *value)
+                    .ok_or_else(|| format!("Missing parameter \"{key}\" for route \"{name}\""))?;
+                used_params.push(key);
+                if is_variadic {
+                    let value: Vec<String> = value.split('/').map(percent_encode).collect();
+                    segments.push(value.join("/"));
+                } else {
+                    segments.push(percent_encode(value));
+                }
+            } else {
+                segments.push(segment.to_string());
+            }
+        }
+
+        let mut url = segments.join("/");
+        let query_params: Vec<(&str, &str)> = params
+            .iter()
+            .filter(|(key, _)| !used_params.contains(key))
+            .copied()
+            .collect();
+        if !query_params.is_empty() {
+            url = url + "?" + &build_query_string(&query_params);
+        }
+        Ok(url)
+    }
+
+    // returns the decoded path parameters if the request path matches this route
+    pub fn match_path(&self, request_path: &str) -> Option<RouteParams> {
+        let request_path = request_path.split('?').next().unwrap_or_default();
+        let route_segments: Vec<&str> = trim_trailing_slash(&self.path).split('/').collect();
+        let request_segments: Vec<&str> = trim_trailing_slash(request_path).split('/').collect();
+        let mut params = RouteParams::new();
+
+        for (index, route_segment) in route_segments.iter().enumerate() {
+            if let Some(key) = route_segment.strip_prefix(':') {
+                if let Some(key) = key.strip_suffix("...") {
+                    let rest = request_segments.get(index..).unwrap_or_default().join("/");
+                    params.insert(key.to_string(), percent_decode(&rest));
+                    return Some(params);
+                }
+                match request_segments.get(index) {
+                    Some(value) if !value.is_empty() => {
+                        params.insert(key.to_string(), percent_decode(value));
+                    }
+                    _ => return None,
+                }
+            } else if request_segments.get(index) != Some(route_segment) {
+                return None;
+            }
+        }
+
+        if route_segments.len() == request_segments.len() {
+            Some(params)
+        } else {
+            None
+        }
+    }
+}
+
+fn trim_trailing_slash(path: &str) -> &str {
+    if path.len() > 1 {
+        path.strip_suffix('/').unwrap_or(path)
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::ToNode;
+
+    fn router(default: RouterPath) -> RouterView {
+        let mut router = RouterView::new();
+        router
+            .route(
+                "/",
+                vec![default, RouterPath::new("/about", |_, _| "about".to_node())],
+            )
+            .unwrap();
+        router
+    }
+
+    #[test]
+    fn named_routes_build_urls() {
+        let mut router = RouterView::new();
+        router
+            .route(
+                "/",
+                vec![
+                    RouterPath::new("/", |_, _| "home".to_node()),
+                    RouterPath::new("/users/:id", |_, _| "user".to_node()).name("user"),
+                    RouterPath::new("/files/:path...", |_, _| "file".to_node()).name("file"),
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            router.url_for("user", &[("id", "a b"), ("tab", "x&y")]),
+            Ok("/users/a%20b?tab=x%26y".to_string())
+        );
+        assert_eq!(
+            router.url_for("file", &[("path", "docs/a?.txt")]),
+            Ok("/files/docs/a%3F.txt".to_string())
+        );
+        assert_eq!(
+            router.url_for("user", &[]),
+            Err("Missing parameter \"id\" for route \"user\"".to_string())
+        );
+        assert_eq!(
+            router.url_for("post", &[]),
+            Err("Unknown route name \"post\"".to_string())
+        );
+    }
+
+    #[test]
+    fn urls_match_their_route() {
+        let route = RouterPath::new("/posts/:year/:slug", |_, _| "post".to_node());
+        let url = route.to_url(&[("year", "2024"), ("slug", "a/b")]).unwrap();
+        assert_eq!(url, "/posts/2024/a%2Fb");
+        let params = route.match_path(&url).unwrap();
+        assert_eq!(params["slug"], "a/b");
+        // unnamed routes are named by their path in errors
+        assert_eq!(
+            route.to_url(&[("year", "2024")]),
+            Err("Missing parameter \"slug\" for route \"/posts/:year/:slug\"".to_string())
+        );
+    }
+
+    #[test]
+    fn duplicate_route_names_are_rejected() {
+        let mut router = RouterView::new();
+        let result = router.route(
+            "/",
+            vec![
+                RouterPath::new("/", |_, _| "a".to_node()).name("home"),
+                RouterPath::new("/b", |_, _| "b".to_node()).name("home"),
+            ],
+        );
+        assert_eq!(result, Err("Duplicate route name \"home\"".to_string()));
+    }
+
+    #[test]
+    #[allow(clippy::result_large_err)]
+    fn guards_block_with_their_response() {
+        let mut router = RouterView::new();
+        let admin_only = Guard::redirect_unless("admin", "/login", |context, _| {
+            context.cookie("role") == Some("admin")
+        });
+        router
+            .route(
+                "/",
+                vec![
+                    RouterPath::new("/", |_, _| "home".to_node()),
+                    RouterPath::new("/admin/:page", |_, _| "admin".to_node()).guard(admin_only),
+                ],
+            )
+            .unwrap();
+        let result = router.render(&Context::new("/admin/users"));
+        assert!(matches!(result, RouteResult::Blocked(_)));
+        assert_eq!(result.response().get_header("location"), Some("/login"));
+        let context = Context::builder("/admin/users")
+            .header("cookie", "role=admin")
+            .build();
+        assert!(matches!(router.render(&context), RouteResult::Found(_)));
+        // router guards run first, for every route
+        router.guard(Guard::new("closed", |_, _| {
+            Err(Response::new("closed".to_node()).status(503))
+        }));
+        let result = router.render(&context);
+        assert!(matches!(result, RouteResult::Blocked(_)));
+        assert_eq!(result.response().status, 503);
+    }
+
+    #[test]
+    fn client_guards_are_checked_in_onmatch() {
+        let mut router = RouterView::new();
+        let guard =
+            Guard::redirect_unless("auth", "/login", |_, _| true).client("window.user", "/login");
+        router
+            .route(
+                "/",
+                vec![RouterPath::new("/", |_, _| "home".to_node()).guard(guard)],
+            )
+            .unwrap();
+        // the content is only requested when the check passes
+        assert!(router.to_mithril_routes("m").starts_with(
+            "{\"/\":{onmatch:function(args,requestedPath){if(!(window.user)){m.route.set(\"/login\");return new Promise(function(){})}return m.request("
+        ));
+    }
+
+    #[test]
+    fn default_route_fallback_is_not_found() {
+        let router = router(RouterPath::new("/", |_, _| "home".to_node()));
+        let result = router.render(&Context::new("/missing"));
+        assert!(result.is_not_found());
+        assert_eq!(result.response().status, 404);
+    }
+
+    #[test]
+    fn default_route_fallback_keeps_redirects() {
+        let router = router(RouterPath::new("/", |_, _| Response::redirect("/login")));
+        let result = router.render(&Context::new("/missing"));
+        assert!(result.is_not_found());
+        assert_eq!(result.response().status, 302);
+        assert_eq!(result.response().get_header("location"), Some("/login"));
+    }
+
+    #[test]
+    fn not_found_route_keeps_its_status() {
+        let mut router = router(RouterPath::new("/", |_, _| "home".to_node()));
+        router.not_found(|_, _| Response::new("gone".to_node()).status(410));
+        let result = router.render(&Context::new("/missing"));
+        assert_eq!(result.response().status, 410);
+    }
 }
@@ -0,0 +1,64 @@
+// turns the output of Node::to_json into mithril vnodes, no eval needed
+var forse = (function () {
+    // event::FORM_BODY
+    var FORM_BODY = "forse:form-data";
+
+    function action(m, data) {
+        return function (event) {
+            var options = { url: data.url, method: data.method || "GET" };
+            ["params", "headers", "body", "user", "password", "withCredentials"].forEach(function (key) {
+                if (data[key] !== undefined) options[key] = data[key];
+            });
+            if (data.body === FORM_BODY) return submit(m, this, event, options);
+            return m.request(options);
+        };
+    }
+
+    // same as the hyperscript of AjaxRequestOption::form_body
+    function submit(m, form, event, options) {
+        event.preventDefault();
+        options.body = new URLSearchParams(new FormData(form));
+        options.headers = Object.assign({ "X-Requested-With": "XMLHttpRequest" }, options.headers);
+        options.extract = function (xhr) {
+            return xhr;
+        };
+        return m.request(options).then(function (xhr) {
+            if (xhr.status < 200 || xhr.status >= 300) return form.submit();
+            if (m.route.get() === undefined) return window.location.assign(xhr.responseURL);
+            var url = new URL(xhr.responseURL);
+            m.route.set(url.pathname + url.search);
+        });
+    }
+
+    function toVnode(m, tree) {
+        if (tree === null || tree === undefined) return null;
+        if (typeof tree === "string") return tree;
+        if (Array.isArray(tree)) {
+            return tree.map(function (item) {
+                return toVnode(m, item);
+            });
+        }
+        if (tree.html !== undefined) return m.trust(tree.html);
+        var attrs = {};
+        for (var key in tree.attrs) {
+            var value = tree.attrs[key];
+            attrs[key] = value !== null && typeof value === "object" && value.action === "request" ? action(m, value) : value;
+        }
+        if (tree.children === undefined) return m(tree.tag, attrs);
+        return m(tree.tag, attrs, toVnode(m, tree.children));
+    }
+
+    // route resolver content for RouterView::to_mithril_json_routes
+    function load(m, url) {
+        return m.request({ url: url, headers: { Accept: "application/json" } }).then(function (tree) {
+            var content = toVnode(m, tree);
+            return {
+                view: function () {
+                    return content;
+                },
+            };
+        });
+    }
+
+    return { toVnode: toVnode, load: load };
+})();
@@ -0,0 +1,322 @@
+use crate::{
+    csrf,
+    document::Document,
+    node::ToNode,
+    response::Response,
+    router_view::{RouteResult, RouterView},
+    site::{Context, ContextBuilder},
+};
+
+// dispatches `http` requests to a router, full page loads get the document html and ajax requests the hyperscript
+pub struct RouterService {
+    pub router: RouterView,
+    pub document: Document,
+    pub function_name: String,
+    // issues csrf tokens and rejects state-changing requests without one, see `csrf`
+    pub csrf: bool,
+    // e.g. when the server terminates tls itself, behind a proxy x-forwarded-proto is enough
+    pub secure_cookie: bool,
+}
+
+impl RouterService {
+    pub fn new(router: RouterView) -> Self {
+        Self {
+            router,
+            document: Document::new(),
+            function_name: "m".to_string(),
+            csrf: true,
+            secure_cookie: false,
+        }
+    }
+
+    pub fn document(mut self, document: Document) -> Self {
+        self.document = document;
+        self
+    }
+
+    // also used by the handler script of the document
+    pub fn function_name(mut self, function_name: &str) -> Self {
+        self.function_name = function_name.to_string();
+        self.document.function_name = function_name.to_string();
+        self
+    }
+
+    // e.g. when the token comes from the session, see `ContextBuilder::csrf_token`
+    pub fn csrf(mut self, csrf: bool) -> Self {
+        self.csrf = csrf;
+        self
+    }
+
+    pub fn secure_cookie(mut self, secure_cookie: bool) -> Self {
+        self.secure_cookie = secure_cookie;
+        self
+    }
+
+    // the collected body, forms are read from it for the routes and the csrf check,
+    // see `Context::form_params`
+    pub fn handle<B: AsRef<[u8]>>(&self, request: &http::Request<B>) -> http::Response<String> {
+        let context = self.context_builder(request).build();
+        self.handle_with_context(&context)
+    }
+
+    // the context `handle` uses, with the body and the csrf token, e.g. to add extensions
+    pub fn context_builder<B: AsRef<[u8]>>(&self, request: &http::Request<B>) -> ContextBuilder {
+        let builder = context_builder(request).body(request_body(request));
+        if self.csrf {
+            builder.issue_csrf_token()
+        } else {
+            builder
+        }
+    }
+
+    // with csrf on the context needs a token, e.g. from `RouterService::context_builder`,
+    // otherwise every state-changing request is rejected
+    //
+    // invalid status codes or header values of the route response become a 500 with the error as body,
+    // use `try_handle_with_context` to handle them yourself
+    pub fn handle_with_context(&self, context: &Context) -> http::Response<String> {
+        self.try_handle_with_context(context)
+            .unwrap_or_else(|error| {
+                let mut response = http::Response::new(format!("Invalid response: {error}"));
+                *response.status_mut() = http::StatusCode::INTERNAL_SERVER_ERROR;
+                response
+            })
+    }
+
+    pub fn try_handle_with_context(
+        &self,
+        context: &Context,
+    ) -> Result<http::Response<String>, http::Error> {
+        let response = if self.csrf && !csrf::verify(context) {
+            Response::new("Invalid CSRF token".to_node()).status(403)
+        } else {
+            match self.router.render(context) {
+                RouteResult::Found(response)
+                | RouteResult::Blocked(response)
+                | RouteResult::NotFound(response) => response,
+            }
+        };
+
+        let mut builder = http::Response::builder().status(response.status);
+        for (key, value) in &response.headers {
+            builder = builder.header(key, value);
+        }
+        if let (true, Some(token)) = (context.csrf_token_issued, &context.csrf_token) {
+            let secure = self.secure_cookie || csrf::is_https(context);
+            builder = builder.header("set-cookie", csrf::cookie_header(token, secure));
+        }
+
+        let (content_type, body) =
+            response.render_body(context, &self.document, &self.function_name);
+        // the same url serves html and hyperscript
+        builder = builder.header("vary", "Accept, X-Requested-With");
+        if response.get_header("content-type").is_none() {
+            builder = builder.header("content-type", content_type);
+        }
+
+        builder.body(body)
+    }
+}
+
+// without the body and the csrf token, see `RouterService::context_builder`
+pub fn context_from_request<B>(request: &http::Request<B>) -> Context {
+    context_builder(request).build()
+}
+
+pub fn context_from_request_with_body<B: AsRef<[u8]>>(request: &http::Request<B>) -> Context {
+    context_builder(request).body(request_body(request)).build()
+}
+
+// bodies that aren't utf-8 are left out
+fn request_body<B: AsRef<[u8]>>(request: &http::Request<B>) -> &str {
+    std::str::from_utf8(request.body().as_ref()).unwrap_or_default()
+}
+
+// without the body and the csrf token, see `RouterService::context_builder`
+pub fn context_builder<B>(request: &http::Request<B>) -> ContextBuilder {
+    let uri = request.uri();
+    let mut builder = Context::builder(uri.path()).method(request.method().as_str());
+    if let Some(query) = uri.query() {
+        builder = builder.query_string(query);
+    }
+    for (key, value) in request.headers() {
+        if let Ok(value) = value.to_str() {
+            builder = builder.header(key.as_str(), value);
+        }
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        node::{elem, ToNode},
+        router_view::RouterPath,
+    };
+
+    fn service() -> RouterService {
+        let mut router = RouterView::new();
+        router
+            .route(
+                "/",
+                vec![
+                    RouterPath::new("/", |_, _| elem("h1", None, "home")),
+                    RouterPath::new("/old", |_, _| Response::redirect("/")),
+                    RouterPath::new("/broken", |_, _| {
+                        Response::new("x".to_node()).header("x-bad", "line\nbreak")
+                    }),
+                ],
+            )
+            .unwrap();
+        RouterService::new(router)
+    }
+
+    fn get(uri: &str) -> http::request::Builder {
+        http::Request::get(uri)
+    }
+
+    #[test]
+    fn page_loads_get_the_document() {
+        let response = service().handle(&get("/").body("").unwrap());
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(response.headers()["vary"], "Accept, X-Requested-With");
+        assert!(response.body().starts_with("<!DOCTYPE html>"));
+        assert!(response.body().contains("<h1>home</h1>"));
+    }
+
+    #[test]
+    fn ajax_requests_get_hyperscript() {
+        let request = get("/")
+            .header("x-requested-with", "XMLHttpRequest")
+            .body("")
+            .unwrap();
+        let response = service().handle(&request);
+        assert_eq!(
+            response.headers()["content-type"],
+            "application/javascript; charset=utf-8"
+        );
+        assert_eq!(response.body(), "m(\"h1\",\"home\")");
+    }
+
+    #[test]
+    fn json_is_negotiated_from_accept() {
+        let request = get("/")
+            .header("accept", "application/json")
+            .body("")
+            .unwrap();
+        let response = service().handle(&request);
+        assert_eq!(
+            response.body(),
+            "{\"tag\":\"h1\",\"attrs\":{},\"children\":[\"home\"]}"
+        );
+    }
+
+    #[test]
+    fn redirects_keep_status_and_location() {
+        let response = service().handle(&get("/old").body("").unwrap());
+        assert_eq!(response.status(), 302);
+        assert_eq!(response.headers()["location"], "/");
+        assert_eq!(response.body(), "");
+    }
+
+    #[test]
+    fn unknown_paths_get_the_default_route_with_404() {
+        let response = service().handle(&get("/missing").body("").unwrap());
+        assert_eq!(response.status(), 404);
+        assert!(response.body().contains("<h1>home</h1>"));
+    }
+
+    #[test]
+    fn invalid_responses_report_the_error() {
+        let context = Context::new("/broken");
+        assert!(service().try_handle_with_context(&context).is_err());
+        let response = service().handle_with_context(&context);
+        assert_eq!(response.status(), 500);
+        assert!(response.body().starts_with("Invalid response: "));
+    }
+
+    fn post(uri: &str) -> http::request::Builder {
+        http::Request::post(uri)
+            .header("host", "example.com")
+            .header("cookie", "csrf_token=abc")
+            .header("content-type", "application/x-www-form-urlencoded")
+    }
+
+    #[test]
+    fn page_loads_issue_the_csrf_cookie() {
+        let response = service().handle(&get("/").body("").unwrap());
+        let cookie = response.headers()["set-cookie"].to_str().unwrap();
+        assert!(cookie.starts_with("csrf_token="));
+        assert!(cookie.ends_with("; Path=/; SameSite=Lax; HttpOnly"));
+        assert!(!cookie.contains("Secure"));
+        // an existing cookie is kept
+        let request = get("/")
+            .header("cookie", "csrf_token=abc")
+            .body("")
+            .unwrap();
+        assert!(!service()
+            .handle(&request)
+            .headers()
+            .contains_key("set-cookie"));
+    }
+
+    #[test]
+    fn posts_without_the_token_are_rejected() {
+        let response = service().handle(&post("/").body("name=x").unwrap());
+        assert_eq!(response.status(), 403);
+        let response = service().handle(&post("/").body("name=x&csrf_token=abd").unwrap());
+        assert_eq!(response.status(), 403);
+        // without csrf protection the route handles it
+        let response = service()
+            .csrf(false)
+            .handle(&post("/").body("name=x").unwrap());
+        assert_eq!(response.status(), 200);
+    }
+
+    #[test]
+    fn posts_with_the_token_are_handled() {
+        let response = service().handle(&post("/").body("name=x&csrf_token=abc").unwrap());
+        assert_eq!(response.status(), 200);
+        let request = post("/")
+            .header("x-csrf-token", "abc")
+            .body("name=x")
+            .unwrap();
+        assert_eq!(service().handle(&request).status(), 200);
+    }
+
+    #[test]
+    fn https_requests_get_a_secure_cookie() {
+        let request = get("/")
+            .header("x-forwarded-proto", "https")
+            .body("")
+            .unwrap();
+        let response = service().handle(&request);
+        assert!(response.headers()["set-cookie"]
+            .to_str()
+            .unwrap()
+            .ends_with("; Secure"));
+        let response = service()
+            .secure_cookie(true)
+            .handle(&get("/").body("").unwrap());
+        assert!(response.headers()["set-cookie"]
+            .to_str()
+            .unwrap()
+            .ends_with("; Secure"));
+    }
+
+    #[test]
+    fn contexts_for_handle_with_context_need_the_csrf_token() {
+        let request = post("/").body("name=x&csrf_token=abc").unwrap();
+        let service = service();
+        // was rejected because the token was never read from the cookie
+        let context = service.context_builder(&request).build();
+        assert_eq!(service.handle_with_context(&context).status(), 200);
+        let context = context_from_request_with_body(&request);
+        assert_eq!(service.handle_with_context(&context).status(), 403);
+    }
+}
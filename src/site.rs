@@ -1,3 +1,469 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use crate::{
+    csrf::{generate_token, CSRF_COOKIE},
+    i18n::{Direction, Translations},
+    utils::{parse_query_string, percent_decode},
+};
+
 pub struct Context {
+    // kept percent-encoded so that encoded slashes survive route matching
     pub request_path: String,
+    pub method: String,
+    pub query: Vec<(String, String)>,
+    // keys are lowercase
+    pub headers: HashMap<String, String>,
+    pub cookies: HashMap<String, String>,
+    // empty unless set with `ContextBuilder::body`, e.g. by `server::RouterService::handle`
+    pub body: String,
+    // ordered by preference, parsed from the accept-language header
+    pub accept_language: Vec<String>,
+    // language of the rendered content, from `ContextBuilder::lang`, the translations or accept-language
+    pub lang: Option<String>,
+    // follows `lang` unless set with `ContextBuilder::direction`
+    pub direction: Direction,
+    pub response_format: ResponseFormat,
+    // stamped on every emitted <script> when set
+    pub csp_nonce: Option<String>,
+    // rendered into post forms and non-GET ajax actions when set, see `csrf`
+    pub csrf_token: Option<String>,
+    // not from the request cookie, the response has to set it
+    pub csrf_token_issued: bool,
+    extensions: HashMap<TypeId, Rc<dyn Any>>,
+}
+
+impl Context {
+    pub fn new(request_path: &str) -> Self {
+        Self::builder(request_path).build()
+    }
+
+    pub fn builder(request_path: &str) -> ContextBuilder {
+        ContextBuilder::new(request_path)
+    }
+
+    pub fn query(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(query_key, _)| query_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .get(&key.to_lowercase())
+            .map(|value| value.as_str())
+    }
+
+    pub fn cookie(&self, key: &str) -> Option<&str> {
+        self.cookies.get(key).map(|value| value.as_str())
+    }
+
+    // submitted form fields: the query of get requests, the url-encoded body otherwise
+    pub fn form_params(&self) -> Vec<(String, String)> {
+        if self.method == "GET" || self.method == "HEAD" {
+            return self.query.clone();
+        }
+        let content_type = self.header("content-type").unwrap_or_default();
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        if media_type.is_empty()
+            || media_type.eq_ignore_ascii_case("application/x-www-form-urlencoded")
+        {
+            parse_query_string(&self.body)
+        } else {
+            vec![]
+        }
+    }
+
+    pub fn locale(&self) -> Option<&str> {
+        self.accept_language.first().map(|value| value.as_str())
+    }
+
+    pub fn extension<T: 'static>(&self) -> Option<&T> {
+        self.extensions
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref::<T>())
+    }
+
+    // with the `Rc<Translations>` extension in the `lang` then accept-language order, the key without one
+    pub fn translate(&self, key: &str, args: &[(String, String)]) -> String {
+        let Some(translations) = self.extension::<Rc<Translations>>() else {
+            return key.to_string();
+        };
+        let preferred: Vec<String> = self
+            .lang
+            .iter()
+            .chain(self.accept_language.iter())
+            .cloned()
+            .collect();
+        translations.translate(&preferred, key, args)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResponseFormat {
+    // full html document, for browser navigation
+    Html,
+    // hyperscript javascript expression, for the content requests of the mithril routes
+    Hyperscript,
+    // node tree data for the client runtime, doesn't need eval on the client
+    Json,
+}
+
+impl ResponseFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Html => "text/html; charset=utf-8",
+            Self::Hyperscript => "application/javascript; charset=utf-8",
+            Self::Json => "application/json; charset=utf-8",
+        }
+    }
+
+    // ajax requests get hyperscript unless they accept html explicitly
+    pub fn from_headers(headers: &HashMap<String, String>) -> Self {
+        let accept = headers
+            .get("accept")
+            .map(|value| value.to_lowercase())
+            .unwrap_or_default();
+        let accepts = |media_type: &str| {
+            accept
+                .split(',')
+                .any(|item| item.split(';').next().unwrap_or_default().trim() == media_type)
+        };
+        if accepts("application/javascript") || accepts("text/javascript") {
+            return Self::Hyperscript;
+        }
+        if accepts("application/json") {
+            return Self::Json;
+        }
+        let is_ajax = headers
+            .get("x-requested-with")
+            .is_some_and(|value| value.eq_ignore_ascii_case("XMLHttpRequest"));
+        if is_ajax && !accepts("text/html") {
+            Self::Hyperscript
+        } else {
+            Self::Html
+        }
+    }
+}
+
+pub struct ContextBuilder {
+    request_path: String,
+    query: Vec<(String, String)>,
+    method: String,
+    headers: HashMap<String, String>,
+    body: String,
+    lang: Option<String>,
+    direction: Option<Direction>,
+    response_format: Option<ResponseFormat>,
+    csp_nonce: Option<String>,
+    csrf_token: Option<String>,
+    issue_csrf_token: bool,
+    extensions: HashMap<TypeId, Rc<dyn Any>>,
+}
+
+impl ContextBuilder {
+    // the path can include a query string, it gets merged with the ones added by `query`
+    pub fn new(request_path: &str) -> Self {
+        let (path, query) = match request_path.split_once('?') {
+            Some((path, query)) => (path, parse_query_string(query)),
+            None => (request_path, vec![]),
+        };
+        Self {
+            request_path: path.to_string(),
+            query,
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: String::new(),
+            lang: None,
+            direction: None,
+            response_format: None,
+            csp_nonce: None,
+            csrf_token: None,
+            issue_csrf_token: false,
+            extensions: HashMap::new(),
+        }
+    }
+
+    pub fn method(mut self, method: &str) -> Self {
+        self.method = method.to_uppercase();
+        self
+    }
+
+    pub fn query(mut self, key: &str, value: &str) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn query_string(mut self, query: &str) -> Self {
+        self.query.extend(parse_query_string(query));
+        self
+    }
+
+    // repeated headers are joined with a comma, except for cookies which use "; "
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        let key = key.to_lowercase();
+        let separator = if key == "cookie" { "; " } else { ", " };
+        let value = match self.headers.remove(&key) {
+            Some(previous) => previous + separator + value,
+            None => value.to_string(),
+        };
+        self.headers.insert(key, value);
+        self
+    }
+
+    // raw request body, e.g. url-encoded form fields, see `Context::form_params`
+    pub fn body(mut self, body: &str) -> Self {
+        self.body = body.to_string();
+        self
+    }
+
+    // e.g. from a locale prefix of the path, takes precedence over accept-language
+    pub fn lang(mut self, lang: &str) -> Self {
+        self.lang = Some(lang.to_string());
+        self
+    }
+
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    // overrides the format negotiated from the accept and x-requested-with headers
+    pub fn response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.response_format = Some(response_format);
+        self
+    }
+
+    // the same value has to be sent in the content-security-policy header
+    pub fn csp_nonce(mut self, nonce: &str) -> Self {
+        self.csp_nonce = Some(nonce.to_string());
+        self
+    }
+
+    // e.g. a token kept in the session instead of the csrf cookie
+    pub fn csrf_token(mut self, token: &str) -> Self {
+        self.csrf_token = Some(token.to_string());
+        self
+    }
+
+    // the token of the csrf cookie, a new one if the request doesn't have it
+    pub fn issue_csrf_token(mut self) -> Self {
+        self.issue_csrf_token = true;
+        self
+    }
+
+    pub fn extension<T: 'static>(mut self, value: T) -> Self {
+        self.extensions.insert(TypeId::of::<T>(), Rc::new(value));
+        self
+    }
+
+    pub fn build(self) -> Context {
+        let cookies = self
+            .headers
+            .get("cookie")
+            .map(|value| parse_cookies(value))
+            .unwrap_or_default();
+        let accept_language = self
+            .headers
+            .get("accept-language")
+            .map(|value| parse_accept_language(value))
+            .unwrap_or_default();
+        let response_format = self
+            .response_format
+            .unwrap_or_else(|| ResponseFormat::from_headers(&self.headers));
+        // the locale of the catalog that translations will come from
+        let translations = self
+            .extensions
+            .get(&TypeId::of::<Rc<Translations>>())
+            .and_then(|value| value.downcast_ref::<Rc<Translations>>());
+        let lang = self
+            .lang
+            .or_else(|| {
+                let catalogs = translations?.negotiate(&accept_language);
+                catalogs.first().map(|catalog| catalog.locale.to_owned())
+            })
+            .or_else(|| accept_language.first().cloned());
+        let mut csrf_token_issued = false;
+        let csrf_token = self.csrf_token.or_else(|| {
+            if !self.issue_csrf_token {
+                return None;
+            }
+            let cookie = cookies.get(CSRF_COOKIE).filter(|value| !value.is_empty());
+            cookie.cloned().or_else(|| {
+                // without a token post forms and ajax actions are rejected, not left unprotected
+                let token = generate_token().ok();
+                csrf_token_issued = token.is_some();
+                token
+            })
+        });
+        let direction = self
+            .direction
+            .or_else(|| lang.as_deref().map(Direction::of_locale))
+            .unwrap_or_default();
+        Context {
+            request_path: self.request_path,
+            method: self.method,
+            query: self.query,
+            headers: self.headers,
+            cookies,
+            body: self.body,
+            accept_language,
+            lang,
+            direction,
+            response_format,
+            csp_nonce: self.csp_nonce,
+            csrf_token,
+            csrf_token_issued,
+            extensions: self.extensions,
+        }
+    }
+}
+
+fn parse_cookies(header: &str) -> HashMap<String, String> {
+    let mut cookies = HashMap::new();
+    for item in header.split(';') {
+        if let Some((key, value)) = item.split_once('=') {
+            let value = value.trim().trim_matches('"');
+            cookies.insert(key.trim().to_string(), percent_decode(value));
+        }
+    }
+    cookies
+}
+
+// https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Accept-Language
+fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut languages: Vec<(String, f32)> = vec![];
+    for item in header.split(',') {
+        let mut parts = item.split(';');
+        let language = parts.next().unwrap_or_default().trim();
+        if language.is_empty() || language == "*" {
+            continue;
+        }
+        let quality = parts
+            .find_map(|part| part.trim().strip_prefix("q="))
+            .and_then(|value| value.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if quality > 0.0 {
+            languages.push((language.to_string(), quality));
+        }
+    }
+    // stable sort keeps the header order for equal weights
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    languages
+        .into_iter()
+        .map(|(language, _)| language)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::i18n::Catalog;
+
+    #[test]
+    fn response_formats_are_negotiated() {
+        let format = |items: &[(&str, &str)]| {
+            let headers = items
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            ResponseFormat::from_headers(&headers)
+        };
+        assert_eq!(format(&[]), ResponseFormat::Html);
+        assert_eq!(
+            format(&[("accept", "text/html,application/xhtml+xml;q=0.9")]),
+            ResponseFormat::Html
+        );
+        assert_eq!(
+            format(&[("accept", "Application/JSON; q=0.5")]),
+            ResponseFormat::Json
+        );
+        assert_eq!(
+            format(&[("accept", "text/javascript, application/json")]),
+            ResponseFormat::Hyperscript
+        );
+        assert_eq!(
+            format(&[("x-requested-with", "xmlhttprequest")]),
+            ResponseFormat::Hyperscript
+        );
+        // ajax requests can still ask for html
+        assert_eq!(
+            format(&[
+                ("x-requested-with", "XMLHttpRequest"),
+                ("accept", "text/html")
+            ]),
+            ResponseFormat::Html
+        );
+        // the override wins
+        let context = Context::builder("/")
+            .header("accept", "application/json")
+            .response_format(ResponseFormat::Html)
+            .build();
+        assert_eq!(context.response_format, ResponseFormat::Html);
+    }
+
+    #[test]
+    fn cookies_are_trimmed_and_decoded() {
+        let cookies = parse_cookies("a=1; b = \"x%20y\" ;c=d=e;invalid");
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(cookies["a"], "1");
+        assert_eq!(cookies["b"], "x y");
+        assert_eq!(cookies["c"], "d=e");
+    }
+
+    #[test]
+    fn accepted_languages_are_ordered_by_quality() {
+        assert_eq!(
+            parse_accept_language("fr;q=0.5, de-AT, *;q=0.9, en;q=0.8, it;q=0, nl;q=0.8"),
+            vec!["de-AT", "en", "nl", "fr"]
+        );
+        assert!(parse_accept_language("").is_empty());
+    }
+
+    #[test]
+    fn path_queries_are_merged_with_added_ones() {
+        let context = Context::builder("/search?q=a+b&page=2")
+            .query("page", "3")
+            .query_string("sort=new")
+            .build();
+        assert_eq!(context.request_path, "/search");
+        assert_eq!(context.query("q"), Some("a b"));
+        // the first value wins
+        assert_eq!(context.query("page"), Some("2"));
+        assert_eq!(context.query.len(), 4);
+        assert_eq!(context.query("sort"), Some("new"));
+    }
+
+    #[test]
+    fn repeated_headers_are_joined() {
+        let context = Context::builder("/")
+            .header("Accept", "text/html")
+            .header("accept", "*/*")
+            .header("Cookie", "a=1")
+            .header("cookie", "b=2")
+            .build();
+        assert_eq!(context.header("ACCEPT"), Some("text/html, */*"));
+        assert_eq!(context.header("cookie"), Some("a=1; b=2"));
+        assert_eq!(context.cookie("b"), Some("2"));
+    }
+
+    #[test]
+    fn translations_prefer_the_context_lang() {
+        let translations = Translations::new("en")
+            .catalog(Catalog::parse("en", "hello = Hello").unwrap())
+            .catalog(Catalog::parse("ar", "hello = مرحبا").unwrap());
+        let context = Context::builder("/")
+            .header("Accept-Language", "en")
+            .lang("ar")
+            .extension(Rc::new(translations))
+            .build();
+        assert_eq!(context.translate("hello", &[]), "مرحبا");
+        assert_eq!(context.direction, Direction::Rtl);
+    }
 }
@@ -0,0 +1,345 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    client,
+    document::Document,
+    response::ResponseBody,
+    router_view::{RouteResult, RouterView},
+    site::Context,
+    utils::{escape_html, percent_decode},
+};
+
+pub type ParamsEnumerator = Box<dyn Fn() -> Vec<Vec<(String, String)>>>;
+
+// pre-renders the routes of a router to `<path>/index.html` files, with the node tree next to it as `index.json`
+pub struct StaticSite {
+    pub router: RouterView,
+    pub document: Document,
+    // absolute url of the site root, used for the sitemap
+    pub base_url: String,
+    pub bundle_name: String,
+    params: HashMap<String, ParamsEnumerator>,
+}
+
+#[derive(Default, Debug)]
+pub struct StaticSiteReport {
+    // urls of the written pages
+    pub pages: Vec<String>,
+    // parametric route paths without a params enumerator
+    pub skipped_routes: Vec<String>,
+    // urls that didn't render to a page, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+impl StaticSite {
+    pub fn new(router: RouterView, base_url: &str) -> Self {
+        Self {
+            router,
+            document: Document::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            bundle_name: "app.js".to_string(),
+            params: HashMap::new(),
+        }
+    }
+
+    pub fn document(mut self, document: Document) -> Self {
+        self.document = document;
+        self
+    }
+
+    pub fn bundle_name(mut self, bundle_name: &str) -> Self {
+        self.bundle_name = bundle_name.to_string();
+        self
+    }
+
+    // every returned params list is rendered as one page of the route with this path
+    pub fn params(
+        mut self,
+        route_path: &str,
+        enumerator: impl Fn() -> Vec<Vec<(String, String)>> + 'static,
+    ) -> Self {
+        self.params
+            .insert(route_path.to_string(), Box::new(enumerator));
+        self
+    }
+
+    // fails before writing anything if the bundle name isn't a plain file name
+    pub fn generate(&self, output_dir: &Path) -> io::Result<StaticSiteReport> {
+        let is_file_name = !self.bundle_name.is_empty()
+            && self.bundle_name != "."
+            && self.bundle_name != ".."
+            && !self.bundle_name.contains(['/', '\\']);
+        if !is_file_name {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid bundle name \"{}\"", self.bundle_name),
+            ));
+        }
+        let mut report = StaticSiteReport::default();
+        // page directory to url, a second url for the same directory would overwrite the first
+        let mut written: HashMap<PathBuf, String> = HashMap::new();
+        let document = self
+            .document
+            .clone()
+            .script(&("/".to_string() + &self.bundle_name));
+
+        for route in self.router.routes() {
+            let urls = if route.is_parametric() {
+                let Some(enumerator) = self.params.get(&route.path) else {
+                    report.skipped_routes.push(route.path.to_owned());
+                    continue;
+                };
+                let mut urls = vec![];
+                for params in enumerator() {
+                    let params: Vec<(&str, &str)> = params
+                        .iter()
+                        .map(|(key, value)| (key.as_str(), value.as_str()))
+                        .collect();
+                    match route.to_url(&params) {
+                        Ok(url) => urls.push(url),
+                        Err(error) => report.failed.push((route.path.to_owned(), error)),
+                    }
+                }
+                urls
+            } else {
+                vec![route.path.to_owned()]
+            };
+
+            for url in urls {
+                // a static file server ignores the query, e.g. "/list?page=2" would be "/list"
+                if url.contains('?') {
+                    let reason = "Urls with a query string can't be pre-rendered".to_string();
+                    report.failed.push((url, reason));
+                    continue;
+                }
+                let Some(page_dir) = page_directory(output_dir, &url) else {
+                    report
+                        .failed
+                        .push((url, "Url can't be mapped to a file path".to_string()));
+                    continue;
+                };
+                if let Some(previous) = written.get(&page_dir) {
+                    let reason = format!("Same page as {previous}");
+                    report.failed.push((url, reason));
+                    continue;
+                }
+                let context = Context::new(&url);
+                let response = match self.router.render(&context) {
+                    RouteResult::Found(response) => response,
+                    RouteResult::Blocked(_) => {
+                        report.failed.push((url, "Blocked by a guard".to_string()));
+                        continue;
+                    }
+                    RouteResult::NotFound(_) => {
+                        report.failed.push((url, "No matching route".to_string()));
+                        continue;
+                    }
+                };
+                match &response.body {
+                    ResponseBody::Node(node) if response.status == 200 => {
+                        fs::create_dir_all(&page_dir)?;
+                        fs::write(
+                            page_dir.join("index.html"),
+                            document.to_html(node, &context),
+                        )?;
+                        let json = node.with_style_element(&context).to_json(&context);
+                        fs::write(page_dir.join("index.json"), json)?;
+                        written.insert(page_dir, url.to_owned());
+                        report.pages.push(url);
+                    }
+                    ResponseBody::Node(_) => {
+                        let reason = format!("Rendered with status {}", response.status);
+                        report.failed.push((url, reason));
+                    }
+                    ResponseBody::Redirect(target) => {
+                        report.failed.push((url, format!("Redirects to {target}")));
+                    }
+                }
+            }
+        }
+
+        fs::create_dir_all(output_dir)?;
+        fs::write(output_dir.join(&self.bundle_name), self.to_bundle())?;
+        fs::write(output_dir.join("sitemap.xml"), self.to_sitemap(&report))?;
+        Ok(report)
+    }
+
+    // client runtime and a route table that loads the pre-rendered index.json files
+    pub fn to_bundle(&self) -> String {
+        let function_name = &self.document.function_name;
+        let loader = format!(
+            "return forse.load({function_name},requestedPath.split(\"?\")[0].replace(/\\/?$/,\"/\")+\"index.json\")"
+        );
+        let routes = self.router.mithril_routes(function_name, &loader);
+        let router = self
+            .router
+            .mithril_router(function_name, "document.body", &routes);
+        // pages are served from their real paths, not from the default "#!" prefix
+        format!(
+            "{}\n{function_name}.route.prefix=\"\";\n{router};\n",
+            client::RUNTIME
+        )
+    }
+
+    pub fn to_sitemap(&self, report: &StaticSiteReport) -> String {
+        let urls: String = report
+            .pages
+            .iter()
+            .map(|url| {
+                format!(
+                    "<url><loc>{}</loc></url>",
+                    escape_html(&(self.base_url.to_owned() + url))
+                )
+            })
+            .collect();
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">{urls}</urlset>\n")
+    }
+}
+
+// "/a/b" -> "<output_dir>/a/b", None for urls that would leave the output directory
+fn page_directory(output_dir: &Path, url: &str) -> Option<PathBuf> {
+    let mut directory = output_dir.to_path_buf();
+    for segment in url.split('/').filter(|segment| !segment.is_empty()) {
+        let segment = percent_decode(segment);
+        if segment == "." || segment == ".." || segment.contains(['/', '\\']) {
+            return None;
+        }
+        directory.push(segment);
+    }
+    Some(directory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{node::elem, router_view::RouterPath};
+
+    // removed when dropped, the name keeps parallel tests apart
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("forse-static-site-{}-{name}", std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn site() -> StaticSite {
+        let mut router = RouterView::new();
+        router
+            .route(
+                "/",
+                vec![
+                    RouterPath::new("/", |_, _| elem("h1", None, "home")),
+                    RouterPath::new("/posts/:id", |_, params| {
+                        elem("h1", None, params["id"].to_owned())
+                    }),
+                    RouterPath::new("/drafts/:id", |_, _| elem("h1", None, "draft")),
+                ],
+            )
+            .unwrap();
+        StaticSite::new(router, "https://example.com/")
+    }
+
+    fn params(items: &[&[(&str, &str)]]) -> Vec<Vec<(String, String)>> {
+        items
+            .iter()
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pages_are_written_as_index_files() {
+        let dir = TempDir::new("layout");
+        let site = site().params("/posts/:id", || params(&[&[("id", "a")], &[("id", "b c")]]));
+        let report = site.generate(&dir.0).unwrap();
+        assert_eq!(report.pages, vec!["/", "/posts/a", "/posts/b%20c"]);
+        assert_eq!(report.skipped_routes, vec!["/drafts/:id"]);
+        let html = fs::read_to_string(dir.0.join("posts/b c/index.html")).unwrap();
+        assert!(html.contains("<h1>b c</h1>"));
+        assert!(html.contains("<script src=\"/app.js\"></script>"));
+        assert!(dir.0.join("index.html").is_file());
+        assert!(dir.0.join("posts/a/index.json").is_file());
+        assert!(fs::read_to_string(dir.0.join("app.js"))
+            .unwrap()
+            .starts_with(client::RUNTIME));
+    }
+
+    #[test]
+    fn the_sitemap_lists_the_pages() {
+        let dir = TempDir::new("sitemap");
+        let site = site().params("/posts/:id", || params(&[&[("id", "a&b")]]));
+        site.generate(&dir.0).unwrap();
+        let sitemap = fs::read_to_string(dir.0.join("sitemap.xml")).unwrap();
+        assert_eq!(
+            sitemap,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+            <url><loc>https://example.com/</loc></url>\
+            <url><loc>https://example.com/posts/a%26b</loc></url>\
+            </urlset>\n"
+        );
+    }
+
+    #[test]
+    fn urls_outside_of_the_output_or_with_a_query_are_rejected() {
+        let dir = TempDir::new("rejected");
+        let invalid = site().params("/posts/:id", || {
+            params(&[
+                &[("id", "..")],
+                &[("id", "a"), ("page", "2")],
+                &[("id", "a")],
+            ])
+        });
+        let report = invalid.generate(&dir.0).unwrap();
+        let failed: Vec<&str> = report.failed.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(failed, vec!["/posts/..", "/posts/a?page=2"]);
+        assert_eq!(report.pages, vec!["/", "/posts/a"]);
+        // a second url for the same page is reported instead of overwriting it
+        let duplicates = site().params("/posts/:id", || params(&[&[("id", "a")], &[("id", "a")]]));
+        let report = duplicates.generate(&dir.0).unwrap();
+        assert_eq!(
+            report.failed,
+            vec![("/posts/a".to_string(), "Same page as /posts/a".to_string())]
+        );
+    }
+
+    #[test]
+    fn bundle_names_have_to_be_file_names() {
+        let dir = TempDir::new("bundle");
+        for name in ["../app.js", "js/app.js", "..", ""] {
+            let error = site().bundle_name(name).generate(&dir.0).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+        assert!(!dir.0.exists());
+    }
+
+    #[test]
+    fn page_directories_stay_in_the_output() {
+        let output = Path::new("out");
+        assert_eq!(page_directory(output, "/"), Some(PathBuf::from("out")));
+        assert_eq!(
+            page_directory(output, "/a/b/"),
+            Some(PathBuf::from("out/a/b"))
+        );
+        assert_eq!(page_directory(output, "/a/%2E%2E"), None);
+        assert_eq!(page_directory(output, "/a%2Fb"), None);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    attribute::{AttributeValue, ToAttributeValue},
+    class_list::ClassList,
+};
+
+// css declared next to a component, scoped by a class name derived from the css itself:
+//
+// let card = StyleSheet::new("card")
+//     .declarations("padding: 1em; border: 1px solid #ccc")
+//     .rule("&:hover", "border-color: #888")
+//     .rule("h2", "margin: 0")
+//     .media("(max-width: 600px)", "&", "padding: 0.5em");
+// elem("div", Some(vec![Attribute::from("class", &card)]), ...)
+//
+// the styles of rendered elements are collected in `RenderState::styles`,
+// `Document` puts them in the head and `Response` sends them as a <style> with hyperscript and json
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StyleSheet {
+    pub name: String,
+    rules: Vec<StyleRule>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct StyleRule {
+    media: Option<String>,
+    // `&` is the scoped class, selectors without it apply to descendants
+    selector: String,
+    declarations: String,
+}
+
+impl StyleSheet {
+    // the name is only a readable prefix of the class name
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            rules: vec![],
+        }
+    }
+
+    // declarations of the element itself, e.g. "color: red; padding: 1em"
+    pub fn declarations(self, declarations: &str) -> Self {
+        self.rule("&", declarations)
+    }
+
+    pub fn rule(mut self, selector: &str, declarations: &str) -> Self {
+        self.rules.push(StyleRule {
+            media: None,
+            selector: selector.to_string(),
+            declarations: declarations.to_string(),
+        });
+        self
+    }
+
+    pub fn media(mut self, query: &str, selector: &str, declarations: &str) -> Self {
+        self.rules.push(StyleRule {
+            media: Some(query.to_string()),
+            selector: selector.to_string(),
+            declarations: declarations.to_string(),
+        });
+        self
+    }
+
+    // stable between builds and processes, styles with the same rules share the class
+    pub fn class_name(&self) -> String {
+        let mut hash: u32 = 0x811c9dc5;
+        for rule in &self.rules {
+            let parts = [
+                rule.media.as_deref().unwrap_or_default(),
+                &rule.selector,
+                &rule.declarations,
+            ];
+            for byte in parts.join("\0").bytes().chain([0xff]) {
+                // fnv-1a
+                hash ^= byte as u32;
+                hash = hash.wrapping_mul(0x01000193);
+            }
+        }
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        format!("{name}-{}", to_base36(hash))
+    }
+
+    pub fn to_css(&self) -> String {
+        let class_selector = format!(".{}", self.class_name());
+        let mut result = String::new();
+        for rule in &self.rules {
+            // each part of a selector list is scoped
+            let selector: Vec<String> = split_selector_list(&rule.selector)
+                .into_iter()
+                .map(|item| {
+                    let item = item.trim();
+                    if item.contains('&') {
+                        item.replace('&', &class_selector)
+                    } else {
+                        format!("{class_selector} {item}")
+                    }
+                })
+                .collect();
+            let declarations = rule.declarations.trim().trim_end_matches(';');
+            let css = format!("{}{{{declarations}}}", selector.join(","));
+            match &rule.media {
+                Some(query) => result += &format!("@media {query}{{{css}}}"),
+                None => result += &css,
+            }
+        }
+        result
+    }
+}
+
+// commas inside parentheses, brackets or strings don't separate selectors, e.g. `:is(a, b)`
+fn split_selector_list(selector: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    let mut characters = selector.char_indices();
+    while let Some((index, character)) = characters.next() {
+        match (quote, character) {
+            (_, '\\') => {
+                characters.next();
+            }
+            (Some(open), _) if character == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(character),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, ',') if depth <= 0 => {
+                parts.push(&selector[start..index]);
+                start = index + 1;
+            }
+            (None, _) => {}
+        }
+    }
+    parts.push(&selector[start..]);
+    parts
+}
+
+fn to_base36(mut value: u32) -> String {
+    let digits = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut result = vec![];
+    loop {
+        result.push(digits[(value % 36) as usize] as char);
+        value /= 36;
+        if value == 0 {
+            break;
+        }
+    }
+    result.iter().rev().collect()
+}
+
+// css of the styles without duplicates, in order of first use,
+// `</` is escaped so a `</style` in a selector or string can't end the <style> element
+pub fn styles_css(styles: &[StyleSheet]) -> String {
+    let mut class_names = vec![];
+    let mut result = String::new();
+    for style in styles {
+        let class_name = style.class_name();
+        if !class_names.contains(&class_name) {
+            result += &style.to_css();
+            class_names.push(class_name);
+        }
+    }
+    result.replace("</", "<\\/")
+}
+
+impl ToAttributeValue for StyleSheet {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::ClassList(ClassList::new().class(self))
+    }
+}
+
+impl ToAttributeValue for &StyleSheet {
+    fn to_attribute_value(&self) -> AttributeValue {
+        AttributeValue::ClassList(ClassList::new().class(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selector_lists_split_on_top_level_commas() {
+        let style = StyleSheet::new("x")
+            .rule("h1, h2", "margin: 0")
+            .rule(":is(a, b) span", "color: red")
+            .rule("&:not(.x, .y)", "color: blue")
+            .rule("[data-a=\"1,2\"], [title='a\\',b']", "color: green");
+        let class = format!(".{}", style.class_name());
+        assert_eq!(
+            style.to_css(),
+            format!(
+                "{class} h1,{class} h2{{margin: 0}}\
+                {class} :is(a, b) span{{color: red}}\
+                {class}:not(.x, .y){{color: blue}}\
+                {class} [data-a=\"1,2\"],{class} [title='a\\',b']{{color: green}}"
+            )
+        );
+    }
+}
@@ -1,80 +1,338 @@
+use std::sync::OnceLock;
+
 use fancy_regex::Regex;
 
 use crate::{
-    attribute::{Attribute, AttributeValue},
+    attribute::{normalize_attribute_key, Attribute, AttributeValue, Attributes},
+    class_list::ClassList,
     node::NodeProperties,
 };
 
-pub const HTML_VOID_ELEMENTS: &'static [&str] = &[
+pub const HTML_VOID_ELEMENTS: &[&str] = &[
     "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
     "track", "wbr", "command", "keygen", "menuitem",
 ];
 
+// elements that get their own line in pretty output
+pub const HTML_BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "caption",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "li",
+    "link",
+    "main",
+    "menu",
+    "meta",
+    "nav",
+    "ol",
+    "optgroup",
+    "option",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "ul",
+];
+
+// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inforeign
+pub const SVG_CASED_ELEMENTS: &[&str] = &[
+    "altGlyph",
+    "altGlyphDef",
+    "altGlyphItem",
+    "animateColor",
+    "animateMotion",
+    "animateTransform",
+    "clipPath",
+    "feBlend",
+    "feColorMatrix",
+    "feComponentTransfer",
+    "feComposite",
+    "feConvolveMatrix",
+    "feDiffuseLighting",
+    "feDisplacementMap",
+    "feDistantLight",
+    "feDropShadow",
+    "feFlood",
+    "feFuncA",
+    "feFuncB",
+    "feFuncG",
+    "feFuncR",
+    "feGaussianBlur",
+    "feImage",
+    "feMerge",
+    "feMergeNode",
+    "feMorphology",
+    "feOffset",
+    "fePointLight",
+    "feSpecularLighting",
+    "feSpotLight",
+    "feTile",
+    "feTurbulence",
+    "foreignObject",
+    "glyphRef",
+    "linearGradient",
+    "radialGradient",
+    "textPath",
+];
+
+pub const SVG_CASED_ATTRIBUTES: &[&str] = &[
+    "attributeName",
+    "attributeType",
+    "baseFrequency",
+    "baseProfile",
+    "calcMode",
+    "clipPathUnits",
+    "diffuseConstant",
+    "edgeMode",
+    "filterUnits",
+    "glyphRef",
+    "gradientTransform",
+    "gradientUnits",
+    "kernelMatrix",
+    "kernelUnitLength",
+    "keyPoints",
+    "keySplines",
+    "keyTimes",
+    "lengthAdjust",
+    "limitingConeAngle",
+    "markerHeight",
+    "markerUnits",
+    "markerWidth",
+    "maskContentUnits",
+    "maskUnits",
+    "numOctaves",
+    "pathLength",
+    "patternContentUnits",
+    "patternTransform",
+    "patternUnits",
+    "pointsAtX",
+    "pointsAtY",
+    "pointsAtZ",
+    "preserveAlpha",
+    "preserveAspectRatio",
+    "primitiveUnits",
+    "refX",
+    "refY",
+    "repeatCount",
+    "repeatDur",
+    "requiredExtensions",
+    "requiredFeatures",
+    "specularConstant",
+    "specularExponent",
+    "spreadMethod",
+    "startOffset",
+    "stdDeviation",
+    "stitchTiles",
+    "surfaceScale",
+    "systemLanguage",
+    "tableValues",
+    "targetX",
+    "targetY",
+    "textLength",
+    "viewBox",
+    "viewTarget",
+    "xChannelSelector",
+    "yChannelSelector",
+    "zoomAndPan",
+];
+
+pub const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+pub const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+pub const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+
+// "userId", "user_id" and "UserID" become "user-id"
+pub fn kebab_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        if *c == '_' || *c == '-' || c.is_whitespace() {
+            if !result.is_empty() && !result.ends_with('-') {
+                result.push('-');
+            }
+            continue;
+        }
+        if c.is_uppercase() && index > 0 {
+            let previous = chars[index - 1];
+            let next_is_lowercase = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+            let starts_word = previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lowercase);
+            if starts_word && !result.is_empty() && !result.ends_with('-') {
+                result.push('-');
+            }
+        }
+        result.extend(c.to_lowercase());
+    }
+    result.trim_end_matches('-').to_string()
+}
+
+// returns the name from the list if it matches case insensitively, e.g. "viewbox" -> "viewBox"
+pub fn adjust_case(name: &str, names: &[&str]) -> String {
+    names
+        .iter()
+        .find(|item| item.eq_ignore_ascii_case(name))
+        .map(|item| item.to_string())
+        .unwrap_or_else(|| name.to_string())
+}
+
+// starting one of these closes an open <p>, so they can't be inside one
+pub const HTML_PARAGRAPH_CLOSING_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "search",
+    "section",
+    "table",
+    "ul",
+];
+
+// elements that can only contain phrasing content
+pub const HTML_PHRASING_CONTENT_ELEMENTS: &[&str] = &[
+    "abbr", "b", "bdi", "bdo", "button", "cite", "code", "data", "dfn", "dt", "em", "h1", "h2",
+    "h3", "h4", "h5", "h6", "i", "kbd", "label", "legend", "mark", "meter", "output", "p", "pre",
+    "progress", "q", "s", "samp", "small", "span", "strong", "sub", "summary", "sup", "time", "u",
+    "var",
+];
+
+// content of these is never reformatted
+pub const HTML_PRESERVE_WHITESPACE_ELEMENTS: &[&str] = &["pre", "textarea", "script", "style"];
+
 // https://github.com/MithrilJS/mithril.js/blob/71ce364c54bc501ce4a02f34e8d60271fe4b4905/render/hyperscript.js#L7
+// attributes override the selector and the last of repeated keys wins, classes are merged
 pub fn parse_elem_properties(
     selector: &str,
     attributes: &Option<Vec<Attribute>>,
 ) -> NodeProperties {
+    merge_elem_properties(selector, attributes).0
+}
+
+// errors on keys with different values in the selector and attributes, and on `class` with `className`
+pub fn try_parse_elem_properties(
+    selector: &str,
+    attributes: &Option<Vec<Attribute>>,
+) -> Result<NodeProperties, String> {
+    match merge_elem_properties(selector, attributes) {
+        (properties, conflicts) if conflicts.is_empty() => Ok(properties),
+        (_, mut conflicts) => Err(conflicts.remove(0)),
+    }
+}
+
+// the properties and the conflicts that `parse_elem_properties` resolves, in order
+fn merge_elem_properties(
+    selector: &str,
+    attributes: &Option<Vec<Attribute>>,
+) -> (NodeProperties, Vec<String>) {
     let mut tag = String::new();
-    let mut node_attributes = Vec::new();
-    let mut classes = vec![];
-
-    let regex = Regex::new(
-        r#"(?:(^|#|\.)([^#\.\[\]]+))|(\[(.+?)(?:\s*=\s*("|'|)((?:\\["'\]]|.)*?)\5)?\])"#,
-    )
-    .unwrap();
-
-    for selector_match in regex.find_iter(selector) {
-        let selector_match = selector_match.unwrap().as_str().trim().to_string();
-
-        if selector_match.len() > 0 {
-            let match_type = selector_match.chars().nth(0).unwrap();
-            let match_value: String = selector_match.chars().skip(1).collect();
-
-            match match_type {
-                '.' => classes.push(match_value),
-                '#' => _ = node_attributes.push(Attribute::from("id", match_value)),
-                '[' => {
-                    // todo: can [x] values contain escaped equal signs?
-                    if match_value.contains('=') {
-                        let regex = Regex::new(r#"\[[\s]*(.*)=(.*)[\s]*\]"#).unwrap();
-                        let result = regex.captures(&selector_match).unwrap();
-
-                        if let Some(result) = result {
-                            node_attributes.push(Attribute::from(
-                                result.get(1).unwrap().as_str().trim(),
-                                result.get(2).unwrap().as_str().trim(),
-                            ));
-                        }
-                    } else {
-                        // todo: make sure the value should be like this
-                        let regex = Regex::new(r#"^\[(.*)\]$"#).unwrap();
-                        let result = regex
-                            .captures(&selector_match)
-                            .unwrap()
-                            .unwrap()
-                            .get(1)
-                            .unwrap()
-                            .as_str();
-
-                        node_attributes.push(Attribute::from(result, ""));
-                    }
-
-                    /*
-                    let parts: Vec<String> = match_value
-                        .split("=")
-                        .map(|item| item.to_string())
-                        .collect();
-                    let key = parts.get(0).unwrap();
-                    let other_parts: Vec<String> =
-                        parts.iter().skip(1).map(|item| item.to_string()).collect();
-                    let mut value = other_parts.join("=");
-                    value.pop();
-
-                    attributes.insert(key.to_owned(), value);
-                    */
+    let mut node_attributes = Attributes::new();
+    let mut classes = ClassList::new();
+    let mut conflicts = vec![];
+    // the last of repeated keys wins, like in the attrs object of mithril
+    // returns the conflict with a previous value
+    let mut set_attribute = |key: &str, value: AttributeValue, source: &str| {
+        let previous = node_attributes.insert(Attribute::new(key, value.clone()))?;
+        let key = normalize_attribute_key(key);
+        (!previous.same_as(&value)).then(|| {
+            format!("Conflicting values for attribute \"{key}\" in {source} of \"{selector}\"")
+        })
+    };
+
+    // compileSelector: the tag only comes from the start, [x] is true, [x=] is empty and [class=x] is a class
+    let regex = SELECTOR_REGEX.get_or_init(|| {
+        Regex::new(r#"(?:(^|#|\.)([^#\.\[\]]+))|(\[(.+?)(?:\s*=\s*("|'|)((?:\\["'\]]|.)*?)\5)?\])"#)
+            .unwrap()
+    });
+    for captures in regex.captures_iter(selector) {
+        let Ok(captures) = captures else {
+            break;
+        };
+        let group = |index| captures.get(index).map(|item| item.as_str());
+        match (group(1), group(2)) {
+            (Some(""), Some(value)) => tag = value.to_string(),
+            (Some("#"), Some(value)) => conflicts.extend(set_attribute(
+                "id",
+                AttributeValue::String(value.to_string()),
+                "the selector",
+            )),
+            (Some("."), Some(value)) => classes = classes.class(value),
+            _ => {
+                let Some(key) = group(4) else {
+                    continue;
+                };
+                let value = group(6).map(unescape_selector_value);
+                match (normalize_attribute_key(key).as_str(), value) {
+                    ("class", value) => classes = classes.class(value.unwrap_or_default()),
+                    (key, Some(value)) => conflicts.extend(set_attribute(
+                        key,
+                        AttributeValue::String(value),
+                        "the selector",
+                    )),
+                    (key, None) => conflicts.extend(set_attribute(
+                        key,
+                        AttributeValue::Bool(true),
+                        "the selector",
+                    )),
                 }
-                _ => tag = selector_match,
             }
         }
     }
@@ -82,32 +340,303 @@ pub fn parse_elem_properties(
     let tag = tag.to_string();
 
     if let Some(attributes) = attributes {
+        // execSelector: `className` is ignored when there's a `class`, both come after the selector classes
+        let has_class = attributes
+            .iter()
+            .any(|attribute| normalize_attribute_key(&attribute.key) == "class");
         for attribute in attributes {
-            let key = &attribute.key;
+            let key = &normalize_attribute_key(&attribute.key);
+            let is_class = key == "class" || key == "className";
+            if key == "className" && has_class {
+                conflicts.push(format!(
+                    "Both class and className are set for \"{selector}\""
+                ));
+                continue;
+            }
             match &attribute.value {
-                AttributeValue::String(value) => {
-                    if key == "class" || key == "className" {
-                        classes.push(value.to_owned());
-                    } else {
-                        node_attributes.push(Attribute::new(
-                            &key,
-                            AttributeValue::String(value.to_owned()),
-                        ));
-                    }
-                }
-                _ => {
-                    node_attributes.push(Attribute::new(&key, (&attribute.value).to_owned()));
+                AttributeValue::String(value) if is_class => classes = classes.class(value),
+                AttributeValue::ClassList(list) if is_class => {
+                    classes = classes.merge(list.to_owned());
                 }
+                value => conflicts.extend(set_attribute(key, value.to_owned(), "the attributes")),
             }
         }
     }
 
-    if classes.len() > 0 {
-        node_attributes.push(Attribute::from("class", classes.join(" ")));
+    if !classes.is_empty() {
+        node_attributes.insert(Attribute::from("class", classes.to_class_string()));
     }
 
-    NodeProperties {
+    let properties = NodeProperties {
         tag,
         attributes: node_attributes,
+        styles: classes.styles,
+    };
+    (properties, conflicts)
+}
+
+static SELECTOR_REGEX: OnceLock<Regex> = OnceLock::new();
+
+// escaped quotes, brackets and backslashes, other escapes are kept
+fn unescape_selector_value(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(next @ ('"' | '\'' | '\\' | '[' | ']'))) => {
+                result.push(*next);
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+// same character set as javascript's encodeURIComponent
+pub fn percent_encode(value: &str) -> String {
+    let mut result = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => result.push(byte as char),
+            _ => result.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    result
+}
+
+pub fn build_query_string(params: &[(&str, &str)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| percent_encode(key) + "=" + &percent_encode(value))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+// invalid escape sequences are kept as they are
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        // from_str_radix alone would also take a sign, e.g. "%+1"
+        let hex = bytes.get(index + 1..index + 3).unwrap_or_default();
+        if bytes[index] == b'%' && hex.len() == 2 && hex.iter().all(u8::is_ascii_hexdigit) {
+            let hex = std::str::from_utf8(hex).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                result.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        result.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+pub fn parse_query_string(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let (key, value) = item.split_once('=').unwrap_or((item, ""));
+            (
+                percent_decode(&key.replace('+', " ")),
+                percent_decode(&value.replace('+', " ")),
+            )
+        })
+        .collect()
+}
+
+// double quoted string literal that is valid in both json and javascript, also safe inside <script>
+pub fn to_js_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '<' => result.push_str("\\u003c"),
+            '>' => result.push_str("\\u003e"),
+            '&' => result.push_str("\\u0026"),
+            '\u{2028}' => result.push_str("\\u2028"),
+            '\u{2029}' => result.push_str("\\u2029"),
+            character if (character as u32) < 0x20 => {
+                result.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => result.push(character),
+        }
+    }
+    result.push('"');
+    result
+}
+
+pub fn escape_html(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            character => result.push(character),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // tag and attributes as strings, `true` for boolean attributes
+    fn properties(selector: &str, attributes: Option<Vec<Attribute>>) -> (String, Vec<String>) {
+        let properties = parse_elem_properties(selector, &attributes);
+        let attributes = properties
+            .attributes
+            .iter()
+            .map(|item| match &item.value {
+                AttributeValue::String(value) => format!("{}={value}", item.key),
+                AttributeValue::Bool(value) => format!("{}:{value}", item.key),
+                _ => format!("{}?", item.key),
+            })
+            .collect();
+        (properties.tag, attributes)
+    }
+
+    fn selector(selector: &str) -> (String, Vec<String>) {
+        properties(selector, None)
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    // cases from mithril's hyperscript tests
+    #[test]
+    fn selectors() {
+        let cases: &[(&str, &str, &[&str])] = &[
+            ("div", "div", &[]),
+            ("a#id", "a", &["id=id"]),
+            ("#id", "", &["id=id"]),
+            (".a.b", "", &["class=a b"]),
+            ("div.a#b.c", "div", &["id=b", "class=a c"]),
+            ("input[disabled]", "input", &["disabled:true"]),
+            ("input[value=]", "input", &["value="]),
+            ("a[href=/x]", "a", &["href=/x"]),
+            ("a[href='/x']", "a", &["href=/x"]),
+            ("a[href=\"/x\"]", "a", &["href=/x"]),
+            ("a[title=a b]", "a", &["title=a b"]),
+            ("a[title = x]", "a", &["title=x"]),
+            ("div[class=a].b", "div", &["class=a b"]),
+            ("div[a=1][b=2][a=3]", "div", &["a=3", "b=2"]),
+            ("svg[viewBox=0 0 1 1]", "svg", &["viewBox=0 0 1 1"]),
+            ("div[data-x=a.b#c]", "div", &["data-x=a.b#c"]),
+        ];
+        for (input, tag, attributes) in cases {
+            assert_eq!(
+                selector(input),
+                (tag.to_string(), strings(attributes)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn escaped_selector_values() {
+        let cases: &[(&str, &str)] = &[
+            (r#"a[title="say \"hi\""]"#, r#"title=say "hi""#),
+            (r"a[title='it\'s']", "title=it's"),
+            (r#"a[title='a "b"']"#, r#"title=a "b""#),
+            (r"a[title=a\\b]", r"title=a\b"),
+            (r"a[title=x\]y]", "title=x]y"),
+            (r"a[title='x\]y']", "title=x]y"),
+            (r"a[title=x\[y]", "title=x[y"),
+            (r"a[title='\[x\]']", "title=[x]"),
+            (r"a[title=x\ny]", r"title=x\ny"),
+        ];
+        for (input, attribute) in cases {
+            assert_eq!(selector(input).1, strings(&[attribute]), "{input}");
+        }
+    }
+
+    #[test]
+    fn attributes_override_the_selector() {
+        let attributes = vec![
+            Attribute::from("id", "b"),
+            Attribute::from("class", "c"),
+            Attribute::from("title", "x"),
+        ];
+        assert_eq!(
+            properties("div#a.s[title=y]", Some(attributes)),
+            (
+                "div".to_string(),
+                strings(&["id=b", "title=x", "class=s c"])
+            )
+        );
+        // className is ignored next to class
+        let attributes = vec![
+            Attribute::from("className", "a"),
+            Attribute::from("class", "b"),
+        ];
+        assert_eq!(properties("div", Some(attributes)).1, strings(&["class=b"]));
+        let attributes = vec![Attribute::from("className", "a")];
+        assert_eq!(properties("div", Some(attributes)).1, strings(&["class=a"]));
+    }
+
+    #[test]
+    fn strict_merging_rejects_conflicts() {
+        let attributes = Some(vec![Attribute::from("id", "b")]);
+        assert_eq!(
+            try_parse_elem_properties("div#a", &attributes).err(),
+            Some(
+                "Conflicting values for attribute \"id\" in the attributes of \"div#a\""
+                    .to_string()
+            )
+        );
+        assert!(try_parse_elem_properties("div#b", &attributes).is_ok());
+        let attributes = Some(vec![
+            Attribute::from("class", "a"),
+            Attribute::from("className", "b"),
+        ]);
+        assert!(try_parse_elem_properties("div", &attributes).is_err());
+    }
+
+    #[test]
+    fn attribute_keys_are_case_insensitive() {
+        let attributes = vec![
+            Attribute::from("Class", "b"),
+            Attribute::from("TITLE", "x"),
+            Attribute::from("ViewBox", "0 0 1 1"),
+        ];
+        assert_eq!(
+            properties("div[Title=y][CLASS=a]", Some(attributes)).1,
+            strings(&["title=x", "viewBox=0 0 1 1", "class=a b"])
+        );
+        let attributes = Some(vec![Attribute::from("ID", "b")]);
+        assert!(try_parse_elem_properties("div#a", &attributes).is_err());
+    }
+
+    #[test]
+    fn percent_decoding_needs_two_hex_digits() {
+        assert_eq!(percent_decode("a%20b%2fc"), "a b/c");
+        assert_eq!(percent_decode("%+1%-1%2"), "%+1%-1%2");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%C3%A9"), "é");
     }
 }
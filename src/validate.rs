@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+
+use crate::{
+    attribute::AttributeValue,
+    event::HtmlAction,
+    node::{flatten_children, Node, NodeProperties, TagNode},
+    site::Context,
+    utils::{
+        HTML_BLOCK_ELEMENTS, HTML_PARAGRAPH_CLOSING_ELEMENTS, HTML_PHRASING_CONTENT_ELEMENTS,
+        HTML_VOID_ELEMENTS,
+    },
+};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DiagnosticKind {
+    // `to_html` drops them
+    ChildrenInVoidElement,
+    BlockInParagraph,
+    // onclick redirects inside links are rendered without a link instead
+    NestedAnchor,
+    // links, form controls and other interactive content inside a <button>
+    InteractiveInButton,
+    DuplicateId(String),
+    ListItemOutsideList,
+    MissingAlt,
+}
+
+// also reported by `a11y::check`
+pub(crate) const MISSING_ALT_MESSAGE: &str =
+    "Images need an alt attribute, empty for decorative images";
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    // child indices from the root, fragments and router views are flattened like in `to_html`
+    pub path: Vec<usize>,
+    // readable form of the path, e.g. "div > ul > li:nth-child(2)"
+    pub location: String,
+    pub message: String,
+}
+
+// structural html errors of the rendered tree, in document order
+pub fn validate(node: &Node, context: &Context) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        context,
+        diagnostics: vec![],
+        ids: HashMap::new(),
+    };
+    let roots = flatten_children(std::slice::from_ref(node), context);
+    for (index, root) in roots.iter().enumerate() {
+        let (path, position) = if roots.len() > 1 {
+            (vec![index], Some(element_position(&roots, index)))
+        } else {
+            (vec![], None)
+        };
+        validator.visit(root, &path, position, &[], false);
+    }
+    validator.diagnostics
+}
+
+struct Validator<'a> {
+    context: &'a Context,
+    diagnostics: Vec<Diagnostic>,
+    ids: HashMap<String, Vec<usize>>,
+}
+
+impl Validator<'_> {
+    fn visit(
+        &mut self,
+        node: &Node,
+        path: &[usize],
+        position: Option<usize>,
+        ancestors: &[&str],
+        is_in_anchor: bool,
+    ) {
+        let Node::Tag(TagNode {
+            properties: Some(properties),
+            children,
+        }) = node
+        else {
+            return;
+        };
+        let tag = properties.tag.as_str();
+        // foreign content has its own rules
+        if tag == "svg" || tag == "math" {
+            return;
+        }
+        let location = location(ancestors, tag, position);
+        let mut report = |kind: DiagnosticKind, message: String| {
+            self.diagnostics.push(Diagnostic {
+                kind,
+                path: path.to_vec(),
+                location: location.clone(),
+                message,
+            })
+        };
+
+        let is_redirect = has_redirect(properties);
+        if tag == "a" && is_in_anchor {
+            report(
+                DiagnosticKind::NestedAnchor,
+                "Links can't be nested, onclick redirects are rendered as links except on buttons outside of phrasing content".to_string(),
+            );
+        }
+        if ancestors.contains(&"button") && is_interactive(tag, properties, is_redirect) {
+            let message = if is_redirect && !is_interactive(tag, properties, false) {
+                format!("<{tag}> with an onclick redirect is rendered as a link, which can't be inside a <button>")
+            } else {
+                format!("<{tag}> is interactive content, which can't be inside a <button>")
+            };
+            report(DiagnosticKind::InteractiveInButton, message);
+        }
+        if HTML_PARAGRAPH_CLOSING_ELEMENTS.contains(&tag) && is_in_paragraph(ancestors) {
+            report(
+                DiagnosticKind::BlockInParagraph,
+                format!("<{tag}> closes the enclosing <p>"),
+            );
+        }
+        if tag == "li" {
+            match ancestors.last() {
+                Some(parent_tag) if ["ul", "ol", "menu"].contains(parent_tag) => {}
+                Some(parent_tag) => report(
+                    DiagnosticKind::ListItemOutsideList,
+                    format!("<li> inside <{parent_tag}> instead of a list"),
+                ),
+                None => report(
+                    DiagnosticKind::ListItemOutsideList,
+                    "<li> outside of a list".to_string(),
+                ),
+            }
+        }
+        if tag == "img" && !properties.attributes.iter().any(|item| item.key == "alt") {
+            report(DiagnosticKind::MissingAlt, MISSING_ALT_MESSAGE.to_string());
+        }
+        let children = flatten_children(children, self.context);
+        if HTML_VOID_ELEMENTS.contains(&tag) && !children.is_empty() {
+            report(
+                DiagnosticKind::ChildrenInVoidElement,
+                format!("<{tag}> is a void element, its children aren't rendered"),
+            );
+        }
+        for attribute in &properties.attributes {
+            if let (true, AttributeValue::String(id)) = (attribute.key == "id", &attribute.value) {
+                if let Some(first_path) = self.ids.get(id) {
+                    let message = format!("Id \"{id}\" is already used at {first_path:?}");
+                    report(DiagnosticKind::DuplicateId(id.to_owned()), message);
+                } else {
+                    self.ids.insert(id.to_owned(), path.to_vec());
+                }
+            }
+        }
+
+        let mut child_ancestors = ancestors.to_vec();
+        child_ancestors.push(tag);
+        // the redirect link counts as an ancestor anchor for the children, buttons get a form instead
+        // unless it would close an enclosing <p>, same as `to_html`
+        let is_redirect_link = is_redirect
+            && (tag != "button"
+                || (!ancestors.contains(&"form") && is_in_phrasing_content(ancestors)));
+        let is_child_in_anchor = is_in_anchor || is_redirect_link || tag == "a";
+        for (index, child) in children.iter().enumerate() {
+            let mut child_path = path.to_vec();
+            child_path.push(index);
+            let position = Some(element_position(&children, index));
+            self.visit(
+                child,
+                &child_path,
+                position,
+                &child_ancestors,
+                is_child_in_anchor,
+            );
+        }
+    }
+}
+
+fn has_redirect(properties: &NodeProperties) -> bool {
+    properties.attributes.iter().any(|attribute| {
+        attribute.key.eq_ignore_ascii_case("onclick")
+            && matches!(
+                &attribute.value,
+                AttributeValue::EventAction(action)
+                    if matches!(action.html_action, HtmlAction::Redirect { .. })
+            )
+    })
+}
+
+// https://html.spec.whatwg.org/multipage/dom.html#interactive-content
+fn is_interactive(tag: &str, properties: &NodeProperties, is_redirect: bool) -> bool {
+    let has_attribute = |key: &str| properties.attributes.iter().any(|item| item.key == key);
+    match tag {
+        "button" | "details" | "embed" | "iframe" | "label" | "select" | "textarea" => true,
+        "a" => has_attribute("href") || is_redirect,
+        "audio" | "video" => has_attribute("controls"),
+        "img" => has_attribute("usemap"),
+        "input" => !properties.attributes.iter().any(|item| {
+            item.key == "type"
+                && matches!(&item.value, AttributeValue::String(value) if value.eq_ignore_ascii_case("hidden"))
+        }),
+        _ => is_redirect,
+    }
+}
+
+// the start tag of these elements closes an open <p> up to the nearest of them,
+// https://html.spec.whatwg.org/multipage/parsing.html#has-an-element-in-button-scope
+const BUTTON_SCOPE_ELEMENTS: &[&str] = &[
+    "applet", "button", "caption", "html", "marquee", "object", "table", "td", "template", "th",
+];
+
+fn is_in_paragraph(ancestors: &[&str]) -> bool {
+    ancestors
+        .iter()
+        .rev()
+        .take_while(|tag| !BUTTON_SCOPE_ELEMENTS.contains(tag))
+        .any(|tag| *tag == "p")
+}
+
+fn is_in_phrasing_content(ancestors: &[&str]) -> bool {
+    ancestors
+        .iter()
+        .rev()
+        .find_map(|tag| {
+            if HTML_PHRASING_CONTENT_ELEMENTS.contains(tag) {
+                Some(true)
+            } else if HTML_BLOCK_ELEMENTS.contains(tag) {
+                Some(false)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(false)
+}
+
+// position among the element siblings starting at 1, text doesn't count for :nth-child
+pub(crate) fn element_position(siblings: &[Node], index: usize) -> usize {
+    siblings[..=index]
+        .iter()
+        .filter(|item| {
+            matches!(
+                item,
+                Node::Tag(TagNode {
+                    properties: Some(_),
+                    ..
+                })
+            )
+        })
+        .count()
+}
+
+pub(crate) fn location(ancestors: &[&str], tag: &str, position: Option<usize>) -> String {
+    let mut parts: Vec<String> = ancestors.iter().map(|item| item.to_string()).collect();
+    match position {
+        Some(position) => parts.push(format!("{tag}:nth-child({position})")),
+        None => parts.push(tag.to_string()),
+    }
+    parts.join(" > ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attribute::Attribute,
+        event::EventAction,
+        node::{elem, ToNode},
+    };
+
+    fn kinds(node: &Node) -> Vec<DiagnosticKind> {
+        validate(node, &Context::new("/"))
+            .into_iter()
+            .map(|item| item.kind)
+            .collect()
+    }
+
+    #[test]
+    fn blocks_close_paragraphs_through_phrasing_ancestors() {
+        let node = elem("p", None, elem("span", None, elem("div", None, "x")));
+        assert_eq!(kinds(&node), vec![DiagnosticKind::BlockInParagraph]);
+        // a button starts a new scope, the div doesn't close the p
+        let node = elem("p", None, elem("button", None, elem("div", None, "x")));
+        assert_eq!(kinds(&node), vec![]);
+    }
+
+    #[test]
+    fn list_items_need_a_list() {
+        assert_eq!(
+            kinds(&elem("li", None, "x")),
+            vec![DiagnosticKind::ListItemOutsideList]
+        );
+        assert_eq!(
+            kinds(&elem("div", None, elem("li", None, "x"))),
+            vec![DiagnosticKind::ListItemOutsideList]
+        );
+        assert_eq!(kinds(&elem("ul", None, elem("li", None, "x"))), vec![]);
+    }
+
+    #[test]
+    fn locations_count_element_siblings_only() {
+        let node = elem(
+            "ul",
+            None,
+            (
+                "text",
+                elem("li", None, "a"),
+                "text",
+                elem("li#x", None, "b"),
+                elem("li#x", None, "c"),
+            ),
+        );
+        let diagnostics = validate(&node, &Context::new("/"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, vec![4]);
+        assert_eq!(diagnostics[0].location, "ul > li:nth-child(3)");
+    }
+
+    #[test]
+    fn links_can_not_be_nested() {
+        let redirect = || {
+            vec![Attribute::from(
+                "onclick",
+                EventAction::ajax_default("/b", None),
+            )]
+        };
+        let node = elem(
+            "a[href=/a]",
+            None,
+            elem("span", None, elem("a[href=/b]", None, "x")),
+        );
+        assert_eq!(kinds(&node), vec![DiagnosticKind::NestedAnchor]);
+        // the redirect of the div is rendered as a link around it
+        let node = elem("div", Some(redirect()), elem("a[href=/b]", None, "x"));
+        assert_eq!(kinds(&node), vec![DiagnosticKind::NestedAnchor]);
+        // a button outside of phrasing content gets a form instead
+        let node = elem("button", Some(redirect()), elem("span", None, "x"));
+        assert_eq!(kinds(&node), vec![]);
+    }
+
+    #[test]
+    fn buttons_can_not_contain_interactive_content() {
+        let node = elem(
+            "button",
+            None,
+            (
+                elem("a[href=/a]", None, "a"),
+                elem("input[type=hidden]", None, Node::None),
+                elem("input", None, Node::None),
+                elem("span", None, "x"),
+            ),
+        );
+        let diagnostics = validate(&node, &Context::new("/"));
+        let locations: Vec<_> = diagnostics
+            .iter()
+            .map(|item| item.location.as_str())
+            .collect();
+        assert_eq!(
+            locations,
+            vec!["button > a:nth-child(1)", "button > input:nth-child(3)"]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|item| item.kind == DiagnosticKind::InteractiveInButton));
+    }
+
+    #[test]
+    fn void_elements_can_not_have_children() {
+        let node = elem("div", None, ("a", elem("br", None, "x")));
+        let diagnostics = validate(&node, &Context::new("/"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::ChildrenInVoidElement);
+        assert_eq!(diagnostics[0].path, vec![1]);
+        assert_eq!(diagnostics[0].location, "div > br:nth-child(1)");
+        assert_eq!(
+            diagnostics[0].message,
+            "<br> is a void element, its children aren't rendered"
+        );
+    }
+
+    #[test]
+    fn paths_follow_flattened_fragments() {
+        let node = elem(
+            "div",
+            None,
+            (
+                elem("p", None, "a"),
+                (elem("img", None, Node::None), "text").to_node(),
+                elem(
+                    "ul",
+                    None,
+                    elem("li", None, elem("img#x", None, Node::None)),
+                ),
+            ),
+        );
+        let diagnostics = validate(&node, &Context::new("/"));
+        let paths: Vec<_> = diagnostics
+            .iter()
+            .map(|item| (item.path.clone(), item.location.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (vec![1], "div > img:nth-child(2)"),
+                (vec![3, 0, 0], "div > ul > li > img:nth-child(1)"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,212 @@
+#![cfg(feature = "derive")]
+
+use forse_web::{
+    attribute::{aria, data, Attribute, AttributeValue, ToAttributeEntries},
+    form::{FieldError, FormErrors, FromForm, Submission},
+    node::{table, Node, ToNode},
+    site::Context,
+};
+
+fn html(node: impl ToNode) -> String {
+    node.to_node().to_html(&Context::new("/"))
+}
+
+fn strings(attributes: &[Attribute]) -> Vec<String> {
+    attributes
+        .iter()
+        .map(|item| match &item.value {
+            AttributeValue::String(value) => format!("{}={value}", item.key),
+            _ => format!("{}?", item.key),
+        })
+        .collect()
+}
+
+#[derive(ToAttributeEntries)]
+struct Card {
+    user_id: u32,
+    #[attribute(rename = "kind")]
+    card_type: String,
+    selected: bool,
+    note: Option<String>,
+    #[attribute(skip)]
+    #[allow(dead_code)]
+    cache: String,
+}
+
+#[derive(ToAttributeEntries)]
+struct Tab {
+    role_description: &'static str,
+    labelled_by: Option<&'static str>,
+}
+
+#[test]
+fn struct_fields_become_data_attributes() {
+    let card = Card {
+        user_id: 5,
+        card_type: "note".to_string(),
+        selected: false,
+        note: None,
+        cache: String::new(),
+    };
+    assert_eq!(
+        strings(&data(&card)),
+        ["data-user-id=5", "data-kind=note", "data-selected=false"]
+    );
+    let card = Card {
+        note: Some("x".to_string()),
+        ..card
+    };
+    assert_eq!(strings(&data(&card)).last().unwrap(), "data-note=x");
+}
+
+#[test]
+fn struct_fields_become_aria_attributes() {
+    let tab = Tab {
+        role_description: "slide",
+        labelled_by: Some("title"),
+    };
+    assert_eq!(
+        strings(&aria(&tab)),
+        ["aria-roledescription=slide", "aria-labelledby=title"]
+    );
+    let tab = Tab {
+        labelled_by: None,
+        ..tab
+    };
+    assert_eq!(tab.to_attribute_entries().len(), 1);
+}
+
+#[derive(ToNode)]
+struct User {
+    first_name: String,
+    #[node(label = "E-mail")]
+    email: String,
+    #[node(skip)]
+    #[allow(dead_code)]
+    password: String,
+    #[node(with = "format_age")]
+    age: u32,
+}
+
+fn format_age(age: &u32) -> String {
+    format!("{age} years")
+}
+
+fn user() -> User {
+    User {
+        first_name: "Ada".to_string(),
+        email: "ada@example.com".to_string(),
+        password: "secret".to_string(),
+        age: 36,
+    }
+}
+
+#[test]
+fn to_node_renders_a_definition_list() {
+    assert_eq!(
+        html(user()),
+        "<dl><dt>First name</dt><dd>Ada</dd><dt>E-mail</dt><dd>ada@example.com</dd>\
+         <dt>Age</dt><dd>36 years</dd></dl>"
+    );
+}
+
+#[derive(ToNode)]
+#[node(table)]
+struct Row {
+    name: &'static str,
+    count: Option<u32>,
+}
+
+#[test]
+fn to_node_renders_table_rows() {
+    let rows = [
+        Row {
+            name: "a",
+            count: Some(1),
+        },
+        Row {
+            name: "b",
+            count: None,
+        },
+    ];
+    assert_eq!(
+        html(table(&rows)),
+        "<table><thead><tr><th scope=\"col\">Name</th><th scope=\"col\">Count</th></tr></thead>\
+         <tbody><tr><td>a</td><td>1</td></tr><tr><td>b</td><td></td></tr></tbody></table>"
+    );
+    assert_eq!(
+        html(&rows[0]),
+        "<table><tbody><tr><th scope=\"row\">Name</th><td>a</td></tr>\
+         <tr><th scope=\"row\">Count</th><td>1</td></tr></tbody></table>"
+    );
+}
+
+#[derive(ToNode)]
+#[node(tag = "article.user")]
+struct Article<T> {
+    #[node(tag = "h2")]
+    title: T,
+    body: Node,
+}
+
+#[test]
+fn to_node_renders_markup() {
+    let article = Article {
+        title: "Hello",
+        body: "text".to_node(),
+    };
+    assert_eq!(
+        html(article),
+        "<article class=\"user\"><h2>Hello</h2><div>text</div></article>"
+    );
+}
+
+#[derive(FromForm, PartialEq, Debug)]
+struct Signup {
+    #[form(rename = "e-mail")]
+    email: String,
+    age: u32,
+    newsletter: bool,
+    nickname: Option<String>,
+}
+
+fn submission(values: &[(&str, &str)]) -> Submission {
+    Submission {
+        values: values
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        errors: FormErrors::new(),
+    }
+}
+
+#[test]
+fn from_form_reads_each_field() {
+    let values = submission(&[
+        ("e-mail", "ada@example.com"),
+        ("age", "36"),
+        ("newsletter", "on"),
+    ]);
+    assert_eq!(
+        Signup::from_form(&values),
+        Ok(Signup {
+            email: "ada@example.com".to_string(),
+            age: 36,
+            newsletter: true,
+            nickname: None,
+        })
+    );
+}
+
+#[test]
+fn from_form_collects_all_errors() {
+    let values = submission(&[("age", "old"), ("nickname", "")]);
+    let errors = Signup::from_form(&values).unwrap_err();
+    // the email is an empty string, only the age fails
+    assert_eq!(
+        errors.errors,
+        vec![("age".to_string(), FieldError::TypeMismatch)]
+    );
+    let errors = Signup::from_form(&submission(&[])).unwrap_err();
+    assert_eq!(errors.get("age"), vec![&FieldError::Required]);
+}